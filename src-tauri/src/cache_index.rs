@@ -0,0 +1,473 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::metadata_store;
+use crate::models::{FileDetails, PaletteColor};
+use crate::paths::{get_cache_dir, get_cache_index_path, get_cached_path};
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+/// Archivos hasta este tamaño se leen completos para calcular la huella.
+const FULL_HASH_LIMIT: u64 = 32 * 1024 * 1024;
+
+/// Bytes leídos en cada una de las tres muestras (inicio, centro y final)
+/// de los archivos mayores.
+const SAMPLE_SIZE: u64 = 64 * 1024;
+
+/// Espera antes de escribir el índice a disco, para agrupar cambios.
+const FLUSH_DELAY: Duration = Duration::from_secs(2);
+
+// ==========================================
+// MODELOS
+// ==========================================
+
+/// Índice persistente de la caché de miniaturas (`index.json` en la carpeta de caché).
+///
+/// Las miniaturas se guardan por huella de contenido, no por ruta, así que
+/// renombrar o mover un archivo conserva su miniatura.
+#[derive(Default, Serialize, Deserialize)]
+struct CacheIndex {
+    /// Ruta absoluta → última huella calculada.
+    #[serde(default)]
+    paths: HashMap<String, PathRecord>,
+    /// Huella → miniaturas generadas.
+    #[serde(default)]
+    entries: HashMap<String, CacheEntry>,
+    /// Miniaturas borradas por superar el tamaño máximo (acumulado).
+    #[serde(default)]
+    evictions: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathRecord {
+    size: u64,
+    /// Fecha de modificación en nanosegundos desde UNIX_EPOCH.
+    modified: u64,
+    fingerprint: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Últimas rutas en las que se vio este contenido.
+    #[serde(default)]
+    paths: BTreeSet<String>,
+    /// Variante (`s-fit`, `l-fill`...) → archivo en caché.
+    #[serde(default)]
+    files: BTreeMap<String, CachedFile>,
+    /// Datos técnicos leídos por `get_file_details`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    details: Option<FileDetails>,
+    /// Última paleta calculada por `extract_palette`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    palette: Option<StoredPalette>,
+    /// Hash perceptual (dHash) de la imagen, para buscar parecidos.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    phash: Option<u64>,
+    /// Ningún backend pudo decodificar este contenido. No se reintenta hasta
+    /// que cambie (otra huella) o se limpie la caché.
+    #[serde(default, skip_serializing_if = "is_false")]
+    undecodable: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredPalette {
+    /// Número de colores pedido al calcularla.
+    count: usize,
+    colors: Vec<PaletteColor>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedFile {
+    bytes: u64,
+    /// Último acceso en segundos desde UNIX_EPOCH.
+    #[serde(default)]
+    last_access: u64,
+}
+
+// ==========================================
+// ESTADO Y PERSISTENCIA
+// ==========================================
+
+fn index() -> MutexGuard<'static, CacheIndex> {
+    static INDEX: OnceLock<Mutex<CacheIndex>> = OnceLock::new();
+
+    INDEX
+        .get_or_init(|| Mutex::new(load()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+fn load() -> CacheIndex {
+    fs::read_to_string(get_cache_index_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Programa una escritura del índice; las llamadas seguidas se agrupan en una.
+fn schedule_flush() {
+    static SCHEDULED: AtomicBool = AtomicBool::new(false);
+
+    if SCHEDULED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        std::thread::sleep(FLUSH_DELAY);
+        SCHEDULED.store(false, Ordering::SeqCst);
+        flush();
+    });
+}
+
+/// Escribe el índice a disco inmediatamente.
+pub fn flush() {
+    let json = serde_json::to_string(&*index());
+    if let Ok(json) = json {
+        let _ = fs::create_dir_all(get_cache_dir());
+        let path = get_cache_index_path();
+        if let Err(e) = metadata_store::write_atomic(&path, json.as_bytes()) {
+            eprintln!("No se pudo guardar {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Vuelve a leer el índice desde disco (al cambiar de biblioteca, cada una
+/// tiene su caché). Conviene llamar antes a `flush` con la anterior activa.
+pub fn reload() {
+    *index() = load();
+}
+
+// ==========================================
+// HUELLAS
+// ==========================================
+
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_nanos() as u64;
+    Some((metadata.len(), modified))
+}
+
+/// Tamaño + SHA-256 del archivo completo. Por encima de `FULL_HASH_LIMIT` se
+/// usan tres muestras y la fecha de modificación, para que una edición que
+/// no cambie el tamaño fuera de las muestras dé otra huella.
+fn compute_fingerprint(path: &Path, size: u64, modified: u64) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+
+    let offsets = if size <= FULL_HASH_LIMIT {
+        vec![(0, size)]
+    } else {
+        hasher.update(modified.to_le_bytes());
+        vec![
+            (0, SAMPLE_SIZE),
+            (size / 2 - SAMPLE_SIZE / 2, SAMPLE_SIZE),
+            (size - SAMPLE_SIZE, SAMPLE_SIZE),
+        ]
+    };
+
+    for (offset, len) in offsets {
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| e.to_string())?;
+        std::io::copy(&mut (&mut file).take(len), &mut hasher).map_err(|e| e.to_string())?;
+    }
+
+    let digest = format!("{:x}", hasher.finalize());
+    Ok(digest[..40].to_string())
+}
+
+/// Huella de contenido del archivo. Se reutiliza la del índice mientras la
+/// ruta conserve tamaño y fecha de modificación; si no, se recalcula.
+pub fn fingerprint(path: &Path) -> Result<String, String> {
+    let (size, modified) = file_stamp(path).ok_or("No se pudo leer el archivo")?;
+    let key = path.to_string_lossy().to_string();
+
+    if let Some(record) = index().paths.get(&key) {
+        if record.size == size && record.modified == modified {
+            return Ok(record.fingerprint.clone());
+        }
+    }
+
+    let fingerprint = compute_fingerprint(path, size, modified)?;
+
+    let mut idx = index();
+    let previous = idx.paths.insert(
+        key.clone(),
+        PathRecord {
+            size,
+            modified,
+            fingerprint: fingerprint.clone(),
+        },
+    );
+    // El contenido de esta ruta cambió: ya no pertenece a la entrada anterior
+    if let Some(previous) = previous.filter(|p| p.fingerprint != fingerprint) {
+        if let Some(entry) = idx.entries.get_mut(&previous.fingerprint) {
+            entry.paths.remove(&key);
+        }
+    }
+    idx.entries
+        .entry(fingerprint.clone())
+        .or_default()
+        .paths
+        .insert(key);
+    drop(idx);
+
+    schedule_flush();
+    Ok(fingerprint)
+}
+
+// ==========================================
+// ENTRADAS DE CACHÉ
+// ==========================================
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Registra una miniatura recién escrita en caché.
+pub fn record_variant(fingerprint: &str, variant: &str, bytes: u64) {
+    index()
+        .entries
+        .entry(fingerprint.to_string())
+        .or_default()
+        .files
+        .insert(
+            variant.to_string(),
+            CachedFile {
+                bytes,
+                last_access: now_secs(),
+            },
+        );
+    schedule_flush();
+}
+
+/// Datos técnicos guardados para este contenido.
+pub fn details(fingerprint: &str) -> Option<FileDetails> {
+    index().entries.get(fingerprint)?.details.clone()
+}
+
+pub fn record_details(fingerprint: &str, details: &FileDetails) {
+    index()
+        .entries
+        .entry(fingerprint.to_string())
+        .or_default()
+        .details = Some(details.clone());
+    schedule_flush();
+}
+
+/// Paleta guardada para este contenido si se calculó con `count` colores.
+pub fn palette(fingerprint: &str, count: usize) -> Option<Vec<PaletteColor>> {
+    let idx = index();
+    let stored = idx.entries.get(fingerprint)?.palette.as_ref()?;
+    (stored.count == count).then(|| stored.colors.clone())
+}
+
+pub fn record_palette(fingerprint: &str, count: usize, colors: &[PaletteColor]) {
+    index()
+        .entries
+        .entry(fingerprint.to_string())
+        .or_default()
+        .palette = Some(StoredPalette {
+        count,
+        colors: colors.to_vec(),
+    });
+    schedule_flush();
+}
+
+/// Hash perceptual guardado para este contenido.
+pub fn phash(fingerprint: &str) -> Option<u64> {
+    index().entries.get(fingerprint)?.phash
+}
+
+pub fn record_phash(fingerprint: &str, hash: u64) {
+    index()
+        .entries
+        .entry(fingerprint.to_string())
+        .or_default()
+        .phash = Some(hash);
+    schedule_flush();
+}
+
+/// Si este contenido ya falló al decodificarse.
+pub fn is_undecodable(fingerprint: &str) -> bool {
+    index()
+        .entries
+        .get(fingerprint)
+        .is_some_and(|entry| entry.undecodable)
+}
+
+pub fn record_undecodable(fingerprint: &str) {
+    index()
+        .entries
+        .entry(fingerprint.to_string())
+        .or_default()
+        .undecodable = true;
+    schedule_flush();
+}
+
+/// Marca una miniatura como usada ahora (orden LRU).
+pub fn touch(fingerprint: &str, variant: &str) {
+    let mut idx = index();
+    let Some(file) = idx
+        .entries
+        .get_mut(fingerprint)
+        .and_then(|entry| entry.files.get_mut(variant))
+    else {
+        return;
+    };
+    file.last_access = now_secs();
+    drop(idx);
+    schedule_flush();
+}
+
+pub struct IndexStats {
+    pub file_count: usize,
+    pub total_size: u64,
+    pub evictions: u64,
+}
+
+/// Número de miniaturas, bytes ocupados y desalojos según el índice.
+pub fn stats() -> IndexStats {
+    let idx = index();
+    let (file_count, total_size) = idx
+        .entries
+        .values()
+        .flat_map(|entry| entry.files.values())
+        .fold((0, 0), |(count, total), file| {
+            (count + 1, total + file.bytes)
+        });
+    IndexStats {
+        file_count,
+        total_size,
+        evictions: idx.evictions,
+    }
+}
+
+/// Rutas conocidas de la biblioteca sin la miniatura `variant`. Las rutas se
+/// registran al calcular su huella (al arrancar se recorre la biblioteca),
+/// así que no hace falta leer el disco.
+pub fn missing_count(variant: &str) -> usize {
+    let idx = index();
+    idx.paths
+        .values()
+        .filter(|record| {
+            idx.entries
+                .get(&record.fingerprint)
+                .is_none_or(|entry| !entry.files.contains_key(variant))
+        })
+        .count()
+}
+
+/// Borra las miniaturas usadas hace más tiempo hasta que la caché ocupe como
+/// mucho `max_bytes`. Devuelve cuántas se borraron.
+pub fn evict_lru(max_bytes: u64) -> usize {
+    let mut idx = index();
+
+    let mut files: Vec<(u64, String, String, u64)> = idx
+        .entries
+        .iter()
+        .flat_map(|(fingerprint, entry)| {
+            entry.files.iter().map(move |(variant, file)| {
+                (
+                    file.last_access,
+                    fingerprint.clone(),
+                    variant.clone(),
+                    file.bytes,
+                )
+            })
+        })
+        .collect();
+    let mut total: u64 = files.iter().map(|f| f.3).sum();
+    if total <= max_bytes {
+        return 0;
+    }
+
+    files.sort();
+    let mut evicted = 0;
+    for (_, fingerprint, variant, bytes) in files {
+        if total <= max_bytes {
+            break;
+        }
+        let _ = fs::remove_file(get_cached_path(&format!("{}-{}", fingerprint, variant)));
+        if let Some(entry) = idx.entries.get_mut(&fingerprint) {
+            entry.files.remove(&variant);
+        }
+        total = total.saturating_sub(bytes);
+        evicted += 1;
+    }
+
+    idx.evictions += evicted as u64;
+    drop(idx);
+
+    flush();
+    evicted
+}
+
+/// Olvida todas las miniaturas (tras vaciar la carpeta de caché). Las huellas
+/// de las rutas se conservan porque siguen siendo válidas.
+pub fn clear_entries() {
+    for entry in index().entries.values_mut() {
+        entry.files.clear();
+        entry.details = None;
+        entry.palette = None;
+        entry.phash = None;
+        entry.undecodable = false;
+    }
+    flush();
+}
+
+/// Sincroniza el índice con los archivos que existen en la biblioteca y
+/// devuelve los nombres (sin extensión) de las miniaturas que deben conservarse.
+pub fn prune(live_paths: &[PathBuf]) -> HashSet<String> {
+    let mut live = HashSet::new();
+    for path in live_paths {
+        if fingerprint(path).is_ok() {
+            live.insert(path.to_string_lossy().to_string());
+        }
+    }
+
+    let mut idx = index();
+    idx.paths.retain(|path, _| live.contains(path));
+
+    let mut keep = HashSet::new();
+    idx.entries.retain(|fingerprint, entry| {
+        entry.paths.retain(|path| live.contains(path));
+        // Miniaturas borradas a mano fuera de la aplicación
+        entry
+            .files
+            .retain(|variant, _| get_cached_path(&format!("{}-{}", fingerprint, variant)).exists());
+
+        if entry.paths.is_empty() {
+            return false;
+        }
+        for variant in entry.files.keys() {
+            keep.insert(format!("{}-{}", fingerprint, variant));
+        }
+        true
+    });
+    drop(idx);
+
+    flush();
+    keep
+}
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::error::ArchiError;
+use crate::journal::{self, Operation};
+use crate::models::{DuplicateReport, DuplicateSet};
+use crate::paths::{get_cache_base_dir, get_user_data_dir, is_config_file};
+use crate::trash_bin;
+use crate::validation::{client_path, library_path, resolve_path, PathError};
+
+// ==========================================
+// ÁMBITO
+// ==========================================
+
+/// Parte del árbol de ARCHI que se revisa.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DuplicateScope {
+    /// Todo `get_user_data_dir()`.
+    All,
+    /// Un cliente completo (Biblioteca y Recursos).
+    Client { name: String },
+    /// Una carpeta de trabajo en formato `Cliente/Trabajo`.
+    Folder { folder: String },
+}
+
+impl DuplicateScope {
+    fn root(&self) -> Result<PathBuf, PathError> {
+        match self {
+            DuplicateScope::All => Ok(get_user_data_dir()),
+            DuplicateScope::Client { name } => client_path(name),
+            DuplicateScope::Folder { folder } => resolve_path(folder, "Biblioteca"),
+        }
+    }
+}
+
+// ==========================================
+// HELPERS
+// ==========================================
+
+/// Archivos no vacíos bajo `root`, sin ocultos, cachés ni `config.json`.
+fn collect_files(root: &Path) -> Vec<(PathBuf, u64)> {
+    let cache_base = get_cache_base_dir();
+    let walker = WalkDir::new(root).into_iter().filter_entry(|e| {
+        !e.file_name().to_string_lossy().starts_with('.')
+            && !e.path().starts_with(&cache_base)
+            && !is_config_file(e.path())
+    });
+
+    walker
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let size = e.metadata().ok()?.len();
+            (size > 0).then(|| (e.into_path(), size))
+        })
+        .collect()
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Comprueba que de cada archivo de `targets` queda en la biblioteca al menos
+/// una copia idéntica que no está en la lista.
+fn ensure_survivors(targets: &[PathBuf]) -> Result<(), ArchiError> {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for (path, size) in collect_files(&get_user_data_dir()) {
+        by_size.entry(size).or_default().push(path);
+    }
+
+    // Hashes ya calculados de las posibles copias que se conservan
+    let mut hashes: HashMap<PathBuf, Option<String>> = HashMap::new();
+    for target in targets {
+        let size = target
+            .metadata()
+            .map_err(|e| ArchiError::io(target, e))?
+            .len();
+        let hash = sha256_file(target)?;
+
+        let survives = by_size.get(&size).into_iter().flatten().any(|candidate| {
+            let Ok(candidate) = candidate.canonicalize() else {
+                return false;
+            };
+            if targets.contains(&candidate) {
+                return false;
+            }
+            let candidate_hash = hashes
+                .entry(candidate.clone())
+                .or_insert_with(|| sha256_file(&candidate).ok());
+            candidate_hash.as_deref() == Some(hash.as_str())
+        });
+
+        if !survives {
+            return Err(ArchiError::Other(format!(
+                "\"{}\" es la última copia de su contenido y no se envía a la papelera",
+                target.file_name().unwrap_or_default().to_string_lossy()
+            )));
+        }
+    }
+    Ok(())
+}
+
+// ==========================================
+// COMANDOS
+// ==========================================
+
+/// Busca archivos con contenido idéntico. Primero agrupa por tamaño y solo
+/// calcula el SHA-256 de los tamaños repetidos.
+#[tauri::command]
+pub async fn scan_duplicates(scope: DuplicateScope) -> Result<DuplicateReport, ArchiError> {
+    let root = scope.root()?;
+    if !root.is_dir() {
+        return Err(ArchiError::NotFound(root));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let files = collect_files(&root);
+        let scanned_files = files.len();
+
+        let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        for (path, size) in files {
+            by_size.entry(size).or_default().push(path);
+        }
+
+        let mut sets = Vec::new();
+        for (size, paths) in by_size.into_iter().filter(|(_, p)| p.len() > 1) {
+            let mut by_hash: HashMap<String, Vec<String>> = HashMap::new();
+            for path in paths {
+                // Archivos que desaparecen o no se pueden leer durante el escaneo
+                if let Ok(hash) = sha256_file(&path) {
+                    by_hash
+                        .entry(hash)
+                        .or_default()
+                        .push(path.to_string_lossy().to_string());
+                }
+            }
+
+            for (hash, mut paths) in by_hash.into_iter().filter(|(_, p)| p.len() > 1) {
+                paths.sort();
+                sets.push(DuplicateSet {
+                    wasted_bytes: size * (paths.len() as u64 - 1),
+                    hash,
+                    size,
+                    paths,
+                });
+            }
+        }
+
+        sets.sort_by(|a, b| {
+            b.wasted_bytes
+                .cmp(&a.wasted_bytes)
+                .then(a.hash.cmp(&b.hash))
+        });
+        Ok(DuplicateReport {
+            wasted_bytes: sets.iter().map(|s| s.wasted_bytes).sum(),
+            scanned_files,
+            sets,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Envía a la papelera las copias elegidas en el informe de duplicados. Se
+/// vuelven a comparar con el resto de la biblioteca y, si de algún contenido
+/// no quedaría ninguna copia, no se borra nada.
+#[tauri::command]
+pub async fn trash_duplicates(paths: Vec<String>) -> Result<usize, ArchiError> {
+    let mut targets = Vec::with_capacity(paths.len());
+    for path in &paths {
+        if !Path::new(path).is_file() {
+            return Err(ArchiError::NotFound(PathBuf::from(path)));
+        }
+        let target = library_path(path)?;
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+
+    let targets = tauri::async_runtime::spawn_blocking(move || {
+        ensure_survivors(&targets)?;
+        Ok::<_, ArchiError>(targets)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    trash_bin::delete(&targets)?;
+    let count = targets.len();
+    journal::record(Operation::Trash { paths: targets });
+    Ok(count)
+}
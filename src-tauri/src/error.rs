@@ -0,0 +1,165 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::validation::PathError;
+
+// ==========================================
+// ERROR DE LOS COMANDOS
+// ==========================================
+
+/// Error devuelto por los comandos de Tauri. Llega al frontend como
+/// `{ code, message, path }`: `code` es estable para poder reaccionar a cada
+/// caso y `message` es el texto para mostrar al usuario.
+#[derive(Debug)]
+pub enum ArchiError {
+    NotFound(PathBuf),
+    AlreadyExists(PathBuf),
+    PermissionDenied(PathBuf),
+    /// Nombre o ruta rechazados por la capa de validación.
+    InvalidPath(PathError),
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    /// Errores sin un caso propio (formatos, procesos externos...).
+    Other(String),
+}
+
+impl ArchiError {
+    /// Error de E/S sobre `path`, con los casos habituales ya clasificados.
+    pub fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        let path = path.as_ref().to_path_buf();
+        match source.kind() {
+            io::ErrorKind::NotFound => ArchiError::NotFound(path),
+            io::ErrorKind::AlreadyExists => ArchiError::AlreadyExists(path),
+            io::ErrorKind::PermissionDenied => ArchiError::PermissionDenied(path),
+            _ => ArchiError::Io {
+                path: Some(path),
+                source,
+            },
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ArchiError::NotFound(_) => "not_found",
+            ArchiError::AlreadyExists(_) => "already_exists",
+            ArchiError::PermissionDenied(_) => "permission_denied",
+            ArchiError::InvalidPath(PathError::OutsideLibrary(_)) => "outside_library",
+            ArchiError::InvalidPath(_) => "invalid_name",
+            ArchiError::Io { source, .. } => match source.kind() {
+                io::ErrorKind::NotFound => "not_found",
+                io::ErrorKind::AlreadyExists => "already_exists",
+                io::ErrorKind::PermissionDenied => "permission_denied",
+                _ => "io",
+            },
+            ArchiError::Other(_) => "other",
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            ArchiError::NotFound(path)
+            | ArchiError::AlreadyExists(path)
+            | ArchiError::PermissionDenied(path) => Some(path),
+            ArchiError::Io { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Nombre visible del archivo o carpeta para los mensajes.
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .to_string()
+}
+
+impl fmt::Display for ArchiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiError::NotFound(path) => write!(f, "No se encontró \"{}\"", display_name(path)),
+            ArchiError::AlreadyExists(path) => {
+                write!(f, "Ya existe \"{}\"", display_name(path))
+            }
+            ArchiError::PermissionDenied(path) => {
+                write!(f, "Sin permisos para acceder a \"{}\"", display_name(path))
+            }
+            ArchiError::InvalidPath(err) => write!(f, "{}", err),
+            ArchiError::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "Error con \"{}\": {}", display_name(path), source),
+            ArchiError::Io { path: None, source } => write!(f, "{}", source),
+            ArchiError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ArchiError {}
+
+impl Serialize for ArchiError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ArchiError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("path", &self.path().map(|p| p.to_string_lossy()))?;
+        state.end()
+    }
+}
+
+// ==========================================
+// CONVERSIONES
+// ==========================================
+
+impl From<io::Error> for ArchiError {
+    fn from(source: io::Error) -> Self {
+        ArchiError::Io { path: None, source }
+    }
+}
+
+impl From<PathError> for ArchiError {
+    fn from(err: PathError) -> Self {
+        ArchiError::InvalidPath(err)
+    }
+}
+
+/// Los helpers internos siguen usando `String` como error.
+impl From<String> for ArchiError {
+    fn from(message: String) -> Self {
+        ArchiError::Other(message)
+    }
+}
+
+impl From<&str> for ArchiError {
+    fn from(message: &str) -> Self {
+        ArchiError::Other(message.to_string())
+    }
+}
+
+impl From<serde_json::Error> for ArchiError {
+    fn from(err: serde_json::Error) -> Self {
+        ArchiError::Other(err.to_string())
+    }
+}
+
+impl From<trash::Error> for ArchiError {
+    fn from(err: trash::Error) -> Self {
+        match err {
+            // Al recuperar: ya hay algo con ese nombre en el sitio original
+            trash::Error::RestoreCollision { path, .. } => ArchiError::AlreadyExists(path),
+            err => ArchiError::Other(err.to_string()),
+        }
+    }
+}
+
+impl From<tauri::Error> for ArchiError {
+    fn from(err: tauri::Error) -> Self {
+        ArchiError::Other(err.to_string())
+    }
+}
@@ -0,0 +1,627 @@
+use std::fs;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use flate2::read::ZlibDecoder;
+
+use crate::cache_index;
+use crate::error::ArchiError;
+use crate::models::{FileDetails, PageSize};
+use crate::tiff::{Entry, TiffReader};
+use crate::validation::library_path;
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+const TAG_WIDTH: u16 = 256;
+const TAG_HEIGHT: u16 = 257;
+const TAG_BITS_PER_SAMPLE: u16 = 258;
+const TAG_PHOTOMETRIC: u16 = 262;
+const TAG_X_RESOLUTION: u16 = 282;
+const TAG_Y_RESOLUTION: u16 = 283;
+const TAG_RESOLUTION_UNIT: u16 = 296;
+const TAG_ICC_PROFILE: u16 = 34675;
+
+/// Recursos de Photoshop: ResolutionInfo y perfil ICC.
+const PSD_RESOURCE_RESOLUTION: u16 = 1005;
+const PSD_RESOURCE_ICC: u16 = 1039;
+
+/// Límites de lectura para PDF (se recorre el archivo en crudo).
+const MAX_PDF_BYTES: u64 = 64 * 1024 * 1024;
+const MAX_OBJECT_STREAM_BYTES: u64 = 16 * 1024 * 1024;
+
+// ==========================================
+// COMANDOS
+// ==========================================
+
+/// Dimensiones, resolución, modo de color y perfil ICC de un archivo, leídos
+/// de sus cabeceras sin decodificar la imagen. El resultado se guarda en el
+/// índice de la caché junto a las miniaturas.
+#[tauri::command]
+pub async fn get_file_details(path: String) -> Result<FileDetails, ArchiError> {
+    let file_path = Path::new(&path);
+    if !file_path.is_file() {
+        return Err(ArchiError::NotFound(file_path.to_path_buf()));
+    }
+    // Solo archivos dentro de la carpeta de ARCHI
+    let file_path = &library_path(&path)?;
+
+    let fingerprint = cache_index::fingerprint(file_path)?;
+    if let Some(details) = cache_index::details(&fingerprint) {
+        return Ok(details);
+    }
+
+    let details = read_details(file_path)?;
+    cache_index::record_details(&fingerprint, &details);
+    Ok(details)
+}
+
+fn read_details(path: &Path) -> Result<FileDetails, String> {
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut r = BufReader::new(file);
+
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)
+        .map_err(|_| "Archivo demasiado pequeño".to_string())?;
+    r.rewind().map_err(|e| e.to_string())?;
+
+    match &magic {
+        [0xFF, 0xD8, ..] => jpeg_details(&mut r),
+        [0x89, b'P', b'N', b'G'] => png_details(&mut r),
+        b"II*\0" | b"MM\0*" => tiff_details(r),
+        b"8BPS" => psd_details(&mut r),
+        // Los .ai modernos también son PDF
+        b"%PDF" => pdf_details(r),
+        _ => Err("Formato no soportado".to_string()),
+    }
+}
+
+// ==========================================
+// JPEG
+// ==========================================
+
+fn jpeg_details<R: Read + Seek>(r: &mut R) -> Result<FileDetails, String> {
+    let mut details = FileDetails {
+        format: "jpeg".to_string(),
+        ..Default::default()
+    };
+    r.seek(SeekFrom::Start(2)).map_err(|e| e.to_string())?;
+
+    loop {
+        let mut marker = [0u8; 2];
+        if r.read_exact(&mut marker).is_err() {
+            break;
+        }
+        if marker[0] != 0xFF {
+            return Err("JPEG corrupto".to_string());
+        }
+        match marker[1] {
+            // Relleno: el siguiente byte es el marcador
+            0xFF => {
+                r.seek(SeekFrom::Current(-1)).map_err(|e| e.to_string())?;
+                continue;
+            }
+            0x01 | 0xD0..=0xD8 => continue,
+            // Inicio de los datos de imagen: ya no hay más cabeceras
+            0xD9 | 0xDA => break,
+            _ => {}
+        }
+
+        let len = read_u16_be(r)? as u64;
+        if len < 2 {
+            return Err("JPEG corrupto".to_string());
+        }
+        let body_len = len - 2;
+
+        match marker[1] {
+            0xC0..=0xCF if !matches!(marker[1], 0xC4 | 0xC8 | 0xCC) => {
+                let body = read_vec(r, body_len)?;
+                if body.len() >= 6 {
+                    details.bit_depth = Some(body[0] as u16);
+                    details.height = Some(be16(&body[1..3]) as u32);
+                    details.width = Some(be16(&body[3..5]) as u32);
+                    details.color_mode = match body[5] {
+                        1 => Some("Gray".to_string()),
+                        3 => Some("RGB".to_string()),
+                        // CMYK o YCCK (Adobe): en ambos casos es un JPEG CMYK
+                        4 => Some("CMYK".to_string()),
+                        _ => None,
+                    };
+                }
+            }
+            // APP0 JFIF: densidad
+            0xE0 => {
+                let body = read_vec(r, body_len)?;
+                if body.starts_with(b"JFIF\0") && body.len() >= 12 && details.dpi_x.is_none() {
+                    let factor = match body[7] {
+                        1 => Some(1.0),
+                        2 => Some(2.54),
+                        _ => None,
+                    };
+                    if let Some(factor) = factor {
+                        details.dpi_x = Some(be16(&body[8..10]) as f64 * factor);
+                        details.dpi_y = Some(be16(&body[10..12]) as f64 * factor);
+                    }
+                }
+            }
+            // APP1 EXIF: resolución (prevalece sobre JFIF)
+            0xE1 => {
+                let body = read_vec(r, body_len)?;
+                if let Some(exif) = body.strip_prefix(b"Exif\0\0") {
+                    if let Ok(mut tiff) = TiffReader::new(Cursor::new(exif)) {
+                        if let Ok((entries, _)) = tiff.read_ifd(tiff.first_ifd()) {
+                            let (x, y) = tiff_resolution(&mut tiff, &entries);
+                            if x.is_some() {
+                                details.dpi_x = x;
+                                details.dpi_y = y;
+                            }
+                        }
+                    }
+                }
+            }
+            // APP2: perfil ICC (puede venir partido en varios segmentos)
+            0xE2 => {
+                let body = read_vec(r, body_len)?;
+                if body.starts_with(b"ICC_PROFILE\0") {
+                    details.has_icc_profile = true;
+                }
+            }
+            _ => {
+                r.seek(SeekFrom::Current(body_len as i64))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    if details.width.is_none() {
+        return Err("No se encontró la cabecera SOF del JPEG".to_string());
+    }
+    Ok(details)
+}
+
+// ==========================================
+// PNG
+// ==========================================
+
+fn png_details<R: Read + Seek>(r: &mut R) -> Result<FileDetails, String> {
+    let mut details = FileDetails {
+        format: "png".to_string(),
+        ..Default::default()
+    };
+    r.seek(SeekFrom::Start(8)).map_err(|e| e.to_string())?;
+
+    loop {
+        let mut head = [0u8; 8];
+        if r.read_exact(&mut head).is_err() {
+            break;
+        }
+        let len = be32(&head[..4]) as u64;
+        let kind = &head[4..8];
+
+        match kind {
+            b"IHDR" => {
+                let body = read_vec(r, len)?;
+                if body.len() < 10 {
+                    return Err("Cabecera PNG inválida".to_string());
+                }
+                details.width = Some(be32(&body[0..4]));
+                details.height = Some(be32(&body[4..8]));
+                details.bit_depth = Some(body[8] as u16);
+                details.color_mode = match body[9] {
+                    0 | 4 => Some("Gray".to_string()),
+                    2 | 6 => Some("RGB".to_string()),
+                    3 => Some("Indexed".to_string()),
+                    _ => None,
+                };
+            }
+            b"pHYs" => {
+                let body = read_vec(r, len)?;
+                // Unidad 1 = píxeles por metro
+                if body.len() >= 9 && body[8] == 1 {
+                    details.dpi_x = Some((be32(&body[0..4]) as f64 * 0.0254).round());
+                    details.dpi_y = Some((be32(&body[4..8]) as f64 * 0.0254).round());
+                }
+            }
+            b"iCCP" => {
+                details.has_icc_profile = true;
+                r.seek(SeekFrom::Current(len as i64))
+                    .map_err(|e| e.to_string())?;
+            }
+            // Los metadatos relevantes van antes de los datos de imagen
+            b"IDAT" | b"IEND" => break,
+            _ => {
+                r.seek(SeekFrom::Current(len as i64))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+
+        // CRC
+        r.seek(SeekFrom::Current(4)).map_err(|e| e.to_string())?;
+    }
+
+    if details.width.is_none() {
+        return Err("No se encontró la cabecera IHDR del PNG".to_string());
+    }
+    Ok(details)
+}
+
+// ==========================================
+// TIFF
+// ==========================================
+
+fn tiff_details<R: Read + Seek>(r: R) -> Result<FileDetails, String> {
+    let mut tiff = TiffReader::new(r)?;
+    let (entries, _) = tiff.read_ifd(tiff.first_ifd())?;
+
+    let mut first = |tag: u16| {
+        find_entry(&entries, tag)
+            .and_then(|e| tiff.values(e).ok())
+            .and_then(|v| v.first().copied())
+    };
+    let width = first(TAG_WIDTH);
+    let height = first(TAG_HEIGHT);
+    let bits = first(TAG_BITS_PER_SAMPLE).unwrap_or(1);
+    let photometric = first(TAG_PHOTOMETRIC);
+
+    let color_mode = match photometric {
+        Some(0 | 1) if bits == 1 => Some("Bitmap"),
+        Some(0 | 1) => Some("Gray"),
+        Some(2 | 6) => Some("RGB"),
+        Some(3) => Some("Indexed"),
+        Some(5) => Some("CMYK"),
+        Some(8..=10) => Some("Lab"),
+        _ => None,
+    };
+
+    let (dpi_x, dpi_y) = tiff_resolution(&mut tiff, &entries);
+
+    Ok(FileDetails {
+        format: "tiff".to_string(),
+        width,
+        height,
+        dpi_x,
+        dpi_y,
+        color_mode: color_mode.map(str::to_string),
+        bit_depth: Some(bits as u16),
+        has_icc_profile: find_entry(&entries, TAG_ICC_PROFILE).is_some(),
+        pages: Vec::new(),
+    })
+}
+
+/// Resolución en DPI de un IFD (TIFF o EXIF). Sin unidad absoluta no hay DPI.
+fn tiff_resolution<R: Read + Seek>(
+    tiff: &mut TiffReader<R>,
+    entries: &[Entry],
+) -> (Option<f64>, Option<f64>) {
+    let unit = find_entry(entries, TAG_RESOLUTION_UNIT)
+        .and_then(|e| tiff.values(e).ok())
+        .and_then(|v| v.first().copied())
+        .unwrap_or(2);
+    let factor = match unit {
+        2 => 1.0,  // pulgadas
+        3 => 2.54, // centímetros
+        _ => return (None, None),
+    };
+
+    let mut rational = |tag: u16| {
+        find_entry(entries, tag)
+            .and_then(|e| tiff.rationals(e).ok())
+            .and_then(|v| v.first().copied())
+            .map(|v| v * factor)
+    };
+    (rational(TAG_X_RESOLUTION), rational(TAG_Y_RESOLUTION))
+}
+
+fn find_entry(entries: &[Entry], tag: u16) -> Option<&Entry> {
+    entries.iter().find(|e| e.tag == tag)
+}
+
+// ==========================================
+// PSD / PSB
+// ==========================================
+
+fn psd_details<R: Read + Seek>(r: &mut R) -> Result<FileDetails, String> {
+    let header = read_vec(r, 26)?;
+    if header.len() < 26 || &header[..4] != b"8BPS" {
+        return Err("Firma PSD inválida".to_string());
+    }
+
+    let color_mode = match be16(&header[24..26]) {
+        0 => "Bitmap",
+        1 => "Gray",
+        2 => "Indexed",
+        3 => "RGB",
+        4 => "CMYK",
+        7 => "Multichannel",
+        8 => "Duotone",
+        9 => "Lab",
+        _ => "Desconocido",
+    };
+    let mut details = FileDetails {
+        format: "psd".to_string(),
+        height: Some(be32(&header[14..18])),
+        width: Some(be32(&header[18..22])),
+        bit_depth: Some(be16(&header[22..24])),
+        color_mode: Some(color_mode.to_string()),
+        ..Default::default()
+    };
+
+    // Color mode data
+    let color_data_len = read_u32_be(r)?;
+    r.seek(SeekFrom::Current(color_data_len as i64))
+        .map_err(|e| e.to_string())?;
+
+    // Image resources
+    let resources_len = read_u32_be(r)? as u64;
+    let mut consumed = 0u64;
+    while consumed + 12 <= resources_len {
+        let mut signature = [0u8; 4];
+        r.read_exact(&mut signature).map_err(|e| e.to_string())?;
+        if &signature != b"8BIM" {
+            break;
+        }
+        let id = read_u16_be(r)?;
+
+        // Nombre Pascal rellenado a longitud par (incluyendo el byte de longitud)
+        let mut name_len = [0u8; 1];
+        r.read_exact(&mut name_len).map_err(|e| e.to_string())?;
+        let name_total = (name_len[0] as u64 + 1 + 1) & !1;
+        r.seek(SeekFrom::Current(name_total as i64 - 1))
+            .map_err(|e| e.to_string())?;
+
+        let size = read_u32_be(r)? as u64;
+        let padded = (size + 1) & !1;
+
+        match id {
+            PSD_RESOURCE_RESOLUTION if size >= 16 => {
+                let body = read_vec(r, padded)?;
+                if body.len() < 16 {
+                    break;
+                }
+                // Fixed 16.16; unidad 1 = píxeles por pulgada, 2 = por centímetro
+                let factor = |unit: u16| if unit == 2 { 2.54 } else { 1.0 };
+                details.dpi_x =
+                    Some(be32(&body[0..4]) as f64 / 65536.0 * factor(be16(&body[4..6])));
+                details.dpi_y =
+                    Some(be32(&body[8..12]) as f64 / 65536.0 * factor(be16(&body[12..14])));
+            }
+            _ => {
+                if id == PSD_RESOURCE_ICC {
+                    details.has_icc_profile = true;
+                }
+                r.seek(SeekFrom::Current(padded as i64))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+
+        consumed += 4 + 2 + name_total + 4 + padded;
+    }
+
+    Ok(details)
+}
+
+// ==========================================
+// PDF
+// ==========================================
+
+/// Tamaño de las páginas (CropBox o MediaBox, con /Rotate) en orden de
+/// aparición en el archivo. Incluye los objetos comprimidos en object streams.
+fn pdf_details<R: Read>(r: R) -> Result<FileDetails, String> {
+    let mut data = Vec::new();
+    r.take(MAX_PDF_BYTES)
+        .read_to_end(&mut data)
+        .map_err(|e| e.to_string())?;
+
+    let streams = object_streams(&data);
+    let mut segments = vec![data];
+    segments.extend(streams);
+
+    // Valor heredado del nodo /Pages para las páginas que no declaran el suyo
+    let inherited = segments
+        .iter()
+        .flat_map(|segment| find_dictionaries(segment, b"/Pages"))
+        .find_map(page_box);
+
+    let mut pages = Vec::new();
+    for segment in &segments {
+        for dict in find_dictionaries(segment, b"/Page") {
+            let Some([x1, y1, x2, y2]) = page_box(dict).or(inherited) else {
+                continue;
+            };
+            let (mut width, mut height) = ((x2 - x1).abs(), (y2 - y1).abs());
+            if let Some(rotate) = dict_number(dict, b"/Rotate") {
+                if (rotate as i64).rem_euclid(180) == 90 {
+                    std::mem::swap(&mut width, &mut height);
+                }
+            }
+            pages.push(PageSize { width, height });
+        }
+    }
+
+    let first = pages.first().copied();
+    Ok(FileDetails {
+        format: "pdf".to_string(),
+        // Dimensiones de la primera página en puntos
+        width: first.map(|p| p.width.round() as u32),
+        height: first.map(|p| p.height.round() as u32),
+        pages,
+        ..Default::default()
+    })
+}
+
+/// Contenido descomprimido de los `/Type /ObjStm` con FlateDecode.
+fn object_streams(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut streams = Vec::new();
+
+    for dict in find_dictionaries(data, b"/ObjStm") {
+        if find(dict, b"/FlateDecode").is_none() {
+            continue;
+        }
+        // `dict` es un subslice de `data`: el stream empieza tras él
+        let dict_end = dict.as_ptr() as usize - data.as_ptr() as usize + dict.len();
+        let rest = &data[dict_end..];
+        let Some(start) = find(rest, b"stream") else {
+            continue;
+        };
+        let mut body = &rest[start + b"stream".len()..];
+        if body.starts_with(b"\r\n") {
+            body = &body[2..];
+        } else if body.starts_with(b"\n") {
+            body = &body[1..];
+        }
+        let end = find(body, b"endstream").unwrap_or(body.len());
+
+        let mut out = Vec::new();
+        let decoder = ZlibDecoder::new(&body[..end]);
+        // El final puede traer un salto de línea extra: lo descomprimido hasta ahí sirve
+        let _ = decoder.take(MAX_OBJECT_STREAM_BYTES).read_to_end(&mut out);
+        if !out.is_empty() {
+            streams.push(out);
+        }
+    }
+    streams
+}
+
+/// Diccionarios `<< ... >>` que contienen `/Type <type_name>` exacto.
+fn find_dictionaries<'a>(data: &'a [u8], type_name: &[u8]) -> Vec<&'a [u8]> {
+    let mut dicts = Vec::new();
+    let mut pos = 0;
+
+    while let Some(found) = find(&data[pos..], b"/Type") {
+        let type_pos = pos + found;
+        pos = type_pos + b"/Type".len();
+
+        let value = skip_whitespace(&data[pos..]);
+        if !value.starts_with(type_name) {
+            continue;
+        }
+        // `/Page` no debe coincidir con `/Pages`
+        let after = value.get(type_name.len()).copied().unwrap_or(b' ');
+        if after.is_ascii_alphanumeric() {
+            continue;
+        }
+
+        if let Some((start, end)) = enclosing_dictionary(data, type_pos) {
+            dicts.push(&data[start..end]);
+        }
+    }
+    dicts
+}
+
+/// Límites del diccionario más interno que contiene la posición `pos`.
+fn enclosing_dictionary(data: &[u8], pos: usize) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let mut i = pos;
+    let start = loop {
+        if i < 2 {
+            return None;
+        }
+        i -= 1;
+        match &data[i - 1..=i] {
+            b">>" => {
+                depth += 1;
+                i -= 1;
+            }
+            b"<<" if depth == 0 => break i - 1,
+            b"<<" => {
+                depth -= 1;
+                i -= 1;
+            }
+            _ => {}
+        }
+    };
+
+    let mut depth = 0;
+    let mut i = start;
+    while i + 1 < data.len() {
+        match &data[i..i + 2] {
+            b"<<" => {
+                depth += 1;
+                i += 2;
+            }
+            b">>" => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return Some((start, i));
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// CropBox (lo que muestran los visores) o, si no hay, MediaBox.
+fn page_box(dict: &[u8]) -> Option<[f64; 4]> {
+    dict_array(dict, b"/CropBox").or_else(|| dict_array(dict, b"/MediaBox"))
+}
+
+fn dict_array(dict: &[u8], key: &[u8]) -> Option<[f64; 4]> {
+    let pos = find(dict, key)?;
+    let rest = skip_whitespace(&dict[pos + key.len()..]);
+    let rest = rest.strip_prefix(b"[")?;
+    let end = rest.iter().position(|&b| b == b']')?;
+
+    let values: Vec<f64> = std::str::from_utf8(&rest[..end])
+        .ok()?
+        .split_ascii_whitespace()
+        .filter_map(|v| v.parse().ok())
+        .collect();
+    values.try_into().ok()
+}
+
+fn dict_number(dict: &[u8], key: &[u8]) -> Option<f64> {
+    let pos = find(dict, key)?;
+    let rest = skip_whitespace(&dict[pos + key.len()..]);
+    let end = rest
+        .iter()
+        .position(|b| !(b.is_ascii_digit() || *b == b'-' || *b == b'.'))
+        .unwrap_or(rest.len());
+    std::str::from_utf8(&rest[..end]).ok()?.parse().ok()
+}
+
+// ==========================================
+// FUNCIONES AUXILIARES
+// ==========================================
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn skip_whitespace(data: &[u8]) -> &[u8] {
+    let start = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    &data[start..]
+}
+
+fn read_vec<R: Read>(r: &mut R, len: u64) -> Result<Vec<u8>, String> {
+    let mut buf = Vec::new();
+    r.take(len)
+        .read_to_end(&mut buf)
+        .map_err(|e| e.to_string())?;
+    Ok(buf)
+}
+
+fn read_u16_be<R: Read>(r: &mut R) -> Result<u16, String> {
+    let mut buf = [0u8; 2];
+    r.read_exact(&mut buf).map_err(|e| e.to_string())?;
+    Ok(u16::from_be_bytes(buf))
+}
+
+fn read_u32_be<R: Read>(r: &mut R) -> Result<u32, String> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf).map_err(|e| e.to_string())?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+fn be16(b: &[u8]) -> u16 {
+    u16::from_be_bytes([b[0], b[1]])
+}
+
+fn be32(b: &[u8]) -> u32 {
+    u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+}
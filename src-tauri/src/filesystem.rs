@@ -1,21 +1,26 @@
-use crate::models::{Client, FileItem, LayoutPosition, Metadata};
-use crate::paths::{get_config_path, get_user_data_dir};
-use crate::setup_directories::ensure_user_setup;
+use crate::error::ArchiError;
+use crate::journal::{self, Operation};
+use crate::metadata_store;
+use crate::models::{Client, FileItem, LayoutPosition, TransferReport};
+use crate::paths::{find_library, get_config, get_config_path, get_user_data_dir};
+use crate::setup_directories::ensure_client_subfolders;
+use crate::transfer::{self, ConflictPolicy};
+use crate::trash_bin;
+use crate::validation::{child_path, client_path, library_path, resolve_path, validate_name};
 use drag::{self, DragItem, DragMode, Image, Options};
 use std::fs;
 use std::io::Read;
 use std::path::PathBuf;
 use std::process::Command;
 use std::time::SystemTime;
-use tauri::Emitter;
-use walkdir::WalkDir;
+use tauri::{AppHandle, Emitter};
 
 // ==========================================
 // FUNCIONES AUXILIARES
 // ==========================================
 
 // Función auxiliar para detectar tipo por magic bytes
-fn detect_file_type_by_magic(path: &std::path::Path) -> Option<String> {
+pub(crate) fn detect_file_type_by_magic(path: &std::path::Path) -> Option<String> {
     // Intentar abrir el archivo
     let mut file = fs::File::open(path).ok()?;
     let mut buffer = [0u8; 8]; // Leer primeros 8 bytes para identificación
@@ -55,54 +60,36 @@ fn detect_file_type_by_magic(path: &std::path::Path) -> Option<String> {
     None
 }
 
-fn resolve_path(folder: &str, subfolder: &str) -> PathBuf {
-    let user_data_dir = get_user_data_dir();
-    let parts: Vec<&str> = folder.split('/').collect();
-
-    if parts.len() > 1 {
-        let mut p = user_data_dir.join(parts[0]).join(subfolder);
-        for part in &parts[1..] {
-            p = p.join(part);
-        }
-        p
-    } else {
-        user_data_dir.join(folder).join(subfolder)
-    }
-}
-
 // ==========================================
 // GESTIÓN DE CLIENTES
 // ==========================================
 
+/// Clientes de la biblioteca activa; `library` es el nombre de esta.
 #[tauri::command]
-pub fn list_clients() -> Result<Vec<Client>, String> {
+pub fn list_clients() -> Result<Vec<Client>, ArchiError> {
     let user_data_dir = get_user_data_dir();
     let mut clients = Vec::new();
 
     if !user_data_dir.exists() {
         return Ok(vec![]);
     }
+    let library = find_library(&get_config(), &user_data_dir)
+        .map(|root| root.name)
+        .unwrap_or_else(|| "ARCHI".to_string());
 
     if let Ok(entries) = fs::read_dir(&user_data_dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_dir() {
                 if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    if !name.starts_with('.') {
+                    if !name.starts_with('.') && client_path(name).is_ok() {
                         // Verificar metadatos de anclaje (pin)
-                        let metadata_path = path.join(".metadatos.json");
-                        let mut pinned = false;
-                        if metadata_path.exists() {
-                            if let Ok(content) = fs::read_to_string(&metadata_path) {
-                                if let Ok(meta) = serde_json::from_str::<Metadata>(&content) {
-                                    pinned = meta.pin.unwrap_or(false);
-                                }
-                            }
-                        }
+                        let pinned = metadata_store::read(&path).pin.unwrap_or(false);
 
                         clients.push(Client {
                             name: name.to_string(),
                             pinned,
+                            library: library.clone(),
                         });
                     }
                 }
@@ -123,63 +110,75 @@ pub fn list_clients() -> Result<Vec<Client>, String> {
 }
 
 #[tauri::command]
-pub fn create_client(name: String) -> Result<(), String> {
-    let target_path = get_user_data_dir().join(&name);
+pub fn create_client(name: String) -> Result<(), ArchiError> {
+    let target_path = client_path(&validate_name(&name)?)?;
 
     if target_path.exists() {
-        return Err("Ya existe un cliente con ese nombre".to_string());
+        return Err(ArchiError::AlreadyExists(target_path));
     }
 
-    fs::create_dir_all(&target_path).map_err(|e| e.to_string())?;
-    let _ = ensure_user_setup(); // Create subfolders
+    fs::create_dir_all(&target_path).map_err(|e| ArchiError::io(&target_path, e))?;
+    let _ = ensure_client_subfolders(&get_user_data_dir()); // Create subfolders
+    journal::record(Operation::Create { path: target_path });
     Ok(())
 }
 
 #[tauri::command]
-pub fn rename_client(old_name: String, new_name: String) -> Result<(), String> {
-    let base = get_user_data_dir();
-    let old_path = base.join(&old_name);
-    let new_path = base.join(&new_name);
+pub fn rename_client(old_name: String, new_name: String) -> Result<(), ArchiError> {
+    let new_name = validate_name(&new_name)?;
+    let old_path = client_path(&old_name)?;
+    let new_path = client_path(&new_name)?;
 
     if !old_path.exists() {
-        return Err("El cliente original no existe".to_string());
+        return Err(ArchiError::NotFound(old_path));
     }
     if new_path.exists() && old_name.to_lowercase() != new_name.to_lowercase() {
-        return Err("Ya existe un cliente con el nuevo nombre".to_string());
+        return Err(ArchiError::AlreadyExists(new_path));
     }
 
-    fs::rename(old_path, new_path).map_err(|e| e.to_string())?;
+    fs::rename(&old_path, &new_path).map_err(|e| ArchiError::io(&new_path, e))?;
+    journal::record(Operation::Rename {
+        from: old_path,
+        to: new_path,
+    });
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_client(name: String) -> Result<(), String> {
-    let target_path = get_user_data_dir().join(&name);
+pub fn delete_client(name: String) -> Result<(), ArchiError> {
+    let target_path = client_path(&name)?;
     if !target_path.exists() {
-        return Err("El cliente no existe".to_string());
+        return Err(ArchiError::NotFound(target_path));
     }
-    trash::delete(target_path).map_err(|e| e.to_string())?;
+    trash_bin::delete(std::slice::from_ref(&target_path))?;
+    journal::record(Operation::Trash {
+        paths: vec![target_path],
+    });
     Ok(())
 }
 
 #[tauri::command]
-pub fn start_drag_files(window: tauri::WebviewWindow, paths: Vec<String>) -> Result<(), String> {
+pub fn start_drag_files(
+    window: tauri::WebviewWindow,
+    paths: Vec<String>,
+) -> Result<(), ArchiError> {
     let mut files: Vec<std::path::PathBuf> = Vec::new();
     // Clonamos paths para usarlos en el drag, ya que paths se consume en el bucle
     let paths_for_check = paths.clone();
 
     for p in paths {
-        let pb = std::path::PathBuf::from(p);
+        let pb = std::path::PathBuf::from(&p);
         if !pb.is_absolute() {
             continue;
         }
         if !pb.exists() {
             continue;
         }
-        files.push(pb);
+        // Solo archivos dentro de la carpeta de ARCHI
+        files.push(library_path(&p)?);
     }
     if files.is_empty() {
-        return Err("Sin archivos para arrastrar".to_string());
+        return Err("Sin archivos para arrastrar".into());
     }
 
     let item = DragItem::Files(files);
@@ -241,30 +240,13 @@ pub fn start_drag_files(window: tauri::WebviewWindow, paths: Vec<String>) -> Res
 }
 
 #[tauri::command]
-pub fn toggle_pin_client(name: String, pin: bool) -> Result<(), String> {
-    let metadata_path = get_user_data_dir().join(&name).join(".metadatos.json");
-
-    let mut meta = if metadata_path.exists() {
-        fs::read_to_string(&metadata_path)
-            .ok()
-            .and_then(|c| serde_json::from_str::<Metadata>(&c).ok())
-            .unwrap_or(Metadata {
-                fecha: None,
-                pin: None,
-                layout: None,
-            })
-    } else {
-        Metadata {
-            fecha: None,
-            pin: None,
-            layout: None,
-        }
-    };
-
-    meta.pin = Some(pin);
+pub fn toggle_pin_client(name: String, pin: bool) -> Result<(), ArchiError> {
+    let client_dir = client_path(&name)?;
+    if !client_dir.is_dir() {
+        return Err(ArchiError::NotFound(client_dir));
+    }
 
-    let json = serde_json::to_string_pretty(&meta).map_err(|e| e.to_string())?;
-    fs::write(metadata_path, json).map_err(|e| e.to_string())?;
+    metadata_store::update(&client_dir, |meta| meta.pin = Some(pin))?;
     Ok(())
 }
 
@@ -273,23 +255,15 @@ pub fn toggle_pin_client(name: String, pin: bool) -> Result<(), String> {
 // ==========================================
 
 #[tauri::command]
-pub fn list_files(folder: String) -> Result<crate::models::FileList, String> {
-    let folder_path = resolve_path(&folder, "Biblioteca");
+pub fn list_files(folder: String) -> Result<crate::models::FileList, ArchiError> {
+    let folder_path = resolve_path(&folder, "Biblioteca")?;
 
     if !folder_path.exists() || !folder_path.is_dir() {
-        return Err("Carpeta no encontrada".to_string());
+        return Err(ArchiError::NotFound(folder_path));
     }
 
     // Leer layout si existe
-    let meta_path = folder_path.join(".metadatos.json");
-    let mut layout = if meta_path.exists() {
-        fs::read_to_string(&meta_path)
-            .ok()
-            .and_then(|c| serde_json::from_str::<Metadata>(&c).ok())
-            .and_then(|m| m.layout)
-    } else {
-        None
-    };
+    let mut layout = metadata_store::read(&folder_path).layout;
 
     let mut files = Vec::new();
 
@@ -320,16 +294,9 @@ pub fn list_files(folder: String) -> Result<crate::models::FileList, String> {
 
             let mut has_metadata = false;
             if is_dir {
-                let meta_path = path.join(".metadatos.json");
-                if meta_path.exists() {
-                    if let Ok(content) = fs::read_to_string(&meta_path) {
-                        if let Ok(meta) = serde_json::from_str::<Metadata>(&content) {
-                            if let Some(d) = meta.fecha {
-                                folder_date = d;
-                                has_metadata = true;
-                            }
-                        }
-                    }
+                if let Some(d) = metadata_store::read(&path).fecha {
+                    folder_date = d;
+                    has_metadata = true;
                 }
             }
 
@@ -394,28 +361,12 @@ pub fn list_files(folder: String) -> Result<crate::models::FileList, String> {
         layout_map.retain(|id, _| valid_ids.contains(id));
 
         if layout_map.len() != before_len {
-            let mut meta = if meta_path.exists() {
-                fs::read_to_string(&meta_path)
-                    .ok()
-                    .and_then(|c| serde_json::from_str::<Metadata>(&c).ok())
-                    .unwrap_or(Metadata {
-                        fecha: None,
-                        pin: None,
-                        layout: None,
-                    })
-            } else {
-                Metadata {
-                    fecha: None,
-                    pin: None,
-                    layout: None,
+            // Sobre la versión en disco, por si otra ventana guardó entre medias
+            let _ = metadata_store::update(&folder_path, |meta| {
+                if let Some(saved) = meta.layout.as_mut() {
+                    saved.retain(|id, _| valid_ids.contains(id));
                 }
-            };
-
-            meta.layout = Some(layout_map.clone());
-
-            if let Ok(json) = serde_json::to_string_pretty(&meta) {
-                let _ = fs::write(&meta_path, json);
-            }
+            });
         }
     }
 
@@ -423,11 +374,11 @@ pub fn list_files(folder: String) -> Result<crate::models::FileList, String> {
 }
 
 #[tauri::command]
-pub fn read_work_note(folder: String) -> Result<String, String> {
-    let folder_path = resolve_path(&folder, "Biblioteca");
+pub fn read_work_note(folder: String) -> Result<String, ArchiError> {
+    let folder_path = resolve_path(&folder, "Biblioteca")?;
 
     if !folder_path.exists() || !folder_path.is_dir() {
-        return Err("Carpeta no encontrada".to_string());
+        return Err(ArchiError::NotFound(folder_path));
     }
 
     let note_path = folder_path.join(".work-note.md");
@@ -435,15 +386,15 @@ pub fn read_work_note(folder: String) -> Result<String, String> {
         fs::write(&note_path, "").map_err(|e| e.to_string())?;
     }
 
-    fs::read_to_string(&note_path).map_err(|e| e.to_string())
+    fs::read_to_string(&note_path).map_err(|e| ArchiError::io(&note_path, e))
 }
 
 #[tauri::command]
-pub fn save_work_note(folder: String, content: String) -> Result<(), String> {
-    let folder_path = resolve_path(&folder, "Biblioteca");
+pub fn save_work_note(folder: String, content: String) -> Result<(), ArchiError> {
+    let folder_path = resolve_path(&folder, "Biblioteca")?;
 
     if !folder_path.exists() || !folder_path.is_dir() {
-        return Err("Carpeta no encontrada".to_string());
+        return Err(ArchiError::NotFound(folder_path));
     }
 
     let note_path = folder_path.join(".work-note.md");
@@ -451,75 +402,55 @@ pub fn save_work_note(folder: String, content: String) -> Result<(), String> {
         fs::write(&note_path, "").map_err(|e| e.to_string())?;
     }
 
-    fs::write(note_path, content).map_err(|e| e.to_string())
+    fs::write(&note_path, content).map_err(|e| ArchiError::io(&note_path, e))
 }
 
 #[tauri::command]
-pub fn save_work_layout(folder: String, positions: Vec<LayoutPosition>) -> Result<(), String> {
-    let folder_path = resolve_path(&folder, "Biblioteca");
+pub fn save_work_layout(folder: String, positions: Vec<LayoutPosition>) -> Result<(), ArchiError> {
+    let folder_path = resolve_path(&folder, "Biblioteca")?;
 
     if !folder_path.exists() || !folder_path.is_dir() {
-        return Err("Carpeta no encontrada".to_string());
+        return Err(ArchiError::NotFound(folder_path));
     }
 
-    let meta_path = folder_path.join(".metadatos.json");
-
-    let mut meta = if meta_path.exists() {
-        fs::read_to_string(&meta_path)
-            .ok()
-            .and_then(|c| serde_json::from_str::<Metadata>(&c).ok())
-            .unwrap_or(Metadata {
-                fecha: None,
-                pin: None,
-                layout: None,
-            })
-    } else {
-        Metadata {
-            fecha: None,
-            pin: None,
-            layout: None,
-        }
-    };
-
     let mut layout_map = std::collections::HashMap::new();
     for p in positions {
         layout_map.insert(p.id, crate::models::FileLayout { x: p.x, y: p.y });
     }
-    meta.layout = Some(layout_map);
 
-    let json = serde_json::to_string_pretty(&meta).map_err(|e| e.to_string())?;
-    fs::write(meta_path, json).map_err(|e| e.to_string())?;
+    let after = Some(layout_map);
+    let before = metadata_store::update(&folder_path, |meta| {
+        std::mem::replace(&mut meta.layout, after.clone())
+    })?;
+    journal::record(Operation::Layout {
+        dir: folder_path,
+        before,
+        after,
+    });
     Ok(())
 }
 
 #[tauri::command]
-pub fn create_folder(parent: String, name: String) -> Result<(), String> {
-    let target_base = resolve_path(&parent, "Biblioteca");
-    let target_path = target_base.join(&name);
+pub fn create_folder(parent: String, name: String) -> Result<(), ArchiError> {
+    let target_base = resolve_path(&parent, "Biblioteca")?;
+    let target_path = target_base.join(validate_name(&name)?);
 
     if target_path.exists() {
-        return Err("Ya existe una carpeta con ese nombre".to_string());
+        return Err(ArchiError::AlreadyExists(target_path));
     }
 
-    fs::create_dir_all(&target_path).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&target_path).map_err(|e| ArchiError::io(&target_path, e))?;
 
     // Solo las carpetas de trabajo (raíz de Biblioteca para un cliente)
     // crean metadatos y nota por defecto. Las subcarpetas son carpetas "simples".
     let is_work_root = !parent.contains('/');
     if is_work_root {
         // Crear metadatos con la marca de tiempo actual
-        let meta_path = target_path.join(".metadatos.json");
         let now = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap()
             .as_secs() as i64;
-        let meta = Metadata {
-            fecha: Some(now),
-            pin: None,
-            layout: None,
-        };
-        let json = serde_json::to_string_pretty(&meta).map_err(|e| e.to_string())?;
-        fs::write(meta_path, json).map_err(|e| e.to_string())?;
+        metadata_store::update(&target_path, |meta| meta.fecha = Some(now))?;
 
         // Crear archivo de nota de trabajo por defecto
         let note_path = target_path.join(".work-note.md");
@@ -528,485 +459,93 @@ pub fn create_folder(parent: String, name: String) -> Result<(), String> {
         }
     }
 
+    journal::record(Operation::Create { path: target_path });
     Ok(())
 }
 
-fn update_metadata_layout_on_rename(
+pub(crate) fn update_metadata_layout_on_rename(
     base_path: &std::path::Path,
     old_name: &str,
     new_name: &str,
 ) -> Result<(), String> {
-    let meta_path = base_path.join(".metadatos.json");
-    if !meta_path.exists() {
+    let has_position = metadata_store::read(base_path)
+        .layout
+        .is_some_and(|layout| layout.contains_key(old_name));
+    if !has_position {
         return Ok(());
     }
 
-    let mut meta: Metadata = fs::read_to_string(&meta_path)
-        .ok()
-        .and_then(|c| serde_json::from_str(&c).ok())
-        .unwrap_or(Metadata {
-            fecha: None,
-            pin: None,
-            layout: None,
-        });
-
-    if let Some(ref mut layout) = meta.layout {
-        if let Some(pos) = layout.remove(old_name) {
-            layout.insert(new_name.to_string(), pos);
-            let json = serde_json::to_string_pretty(&meta).map_err(|e| e.to_string())?;
-            fs::write(meta_path, json).map_err(|e| e.to_string())?;
+    metadata_store::update(base_path, |meta| {
+        if let Some(layout) = meta.layout.as_mut() {
+            if let Some(pos) = layout.remove(old_name) {
+                layout.insert(new_name.to_string(), pos);
+            }
         }
-    }
-
-    Ok(())
+    })
 }
 
 #[tauri::command]
-pub fn rename_folder(parent: String, old_name: String, new_name: String) -> Result<(), String> {
-    let base_path = resolve_path(&parent, "Biblioteca");
-    let old_path = base_path.join(&old_name);
+pub fn rename_folder(parent: String, old_name: String, new_name: String) -> Result<(), ArchiError> {
+    let new_name = validate_name(&new_name)?;
+    let base_path = resolve_path(&parent, "Biblioteca")?;
+    let old_path = child_path(&base_path, &old_name)?;
     let new_path = base_path.join(&new_name);
 
     if !old_path.exists() {
-        return Err("La carpeta original no existe".to_string());
+        return Err(ArchiError::NotFound(old_path));
     }
     if new_path.exists() && old_name.to_lowercase() != new_name.to_lowercase() {
-        return Err("Ya existe una carpeta con el nuevo nombre".to_string());
+        return Err(ArchiError::AlreadyExists(new_path));
     }
 
-    fs::rename(old_path, new_path).map_err(|e| e.to_string())?;
+    fs::rename(&old_path, &new_path).map_err(|e| ArchiError::io(&new_path, e))?;
 
     // Actualizar layout en metadatos si existe
     let _ = update_metadata_layout_on_rename(&base_path, &old_name, &new_name);
+    journal::record(Operation::Rename {
+        from: old_path,
+        to: new_path,
+    });
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_folder(parent: String, name: String) -> Result<(), String> {
-    let base_path = resolve_path(&parent, "Biblioteca");
-    let target_path = base_path.join(&name);
+pub fn delete_folder(parent: String, name: String) -> Result<(), ArchiError> {
+    let base_path = resolve_path(&parent, "Biblioteca")?;
+    let target_path = child_path(&base_path, &name)?;
 
     if !target_path.exists() {
-        return Err("La carpeta no existe".to_string());
+        return Err(ArchiError::NotFound(target_path));
     }
 
-    trash::delete(target_path).map_err(|e| e.to_string())?;
+    trash_bin::delete(std::slice::from_ref(&target_path))?;
+    journal::record(Operation::Trash {
+        paths: vec![target_path],
+    });
     Ok(())
 }
 
 #[tauri::command]
-pub fn import_dropped_items(
+pub async fn import_dropped_items(
+    app: AppHandle,
     folder: String,
     paths: Vec<String>,
     copy_mode: bool,
-) -> Result<(), String> {
-    println!("Importing items. Copy mode: {}", copy_mode);
-    let dest_dir = resolve_path(&folder, "Biblioteca");
-    if !dest_dir.exists() || !dest_dir.is_dir() {
-        return Err("Carpeta destino no encontrada".to_string());
-    }
-
-    fn unique_dest_path(dest_dir: &std::path::Path, base_name: &str, is_dir: bool) -> PathBuf {
-        let initial = dest_dir.join(base_name);
-        if !initial.exists() {
-            return initial;
-        }
-
-        let (stem, ext) = if !is_dir {
-            match base_name.rsplit_once('.') {
-                Some((s, e)) if !s.is_empty() && !e.is_empty() => {
-                    (s.to_string(), Some(e.to_string()))
-                }
-                _ => (base_name.to_string(), None),
-            }
-        } else {
-            (base_name.to_string(), None)
-        };
-
-        for n in 2..10_000 {
-            let candidate_name = match &ext {
-                Some(e) => format!("{} ({}).{}", stem, n, e),
-                None => format!("{} ({})", stem, n),
-            };
-            let candidate = dest_dir.join(candidate_name);
-            if !candidate.exists() {
-                return candidate;
-            }
-        }
-
-        dest_dir.join(base_name)
-    }
-
-    fn copy_dir_recursive(src_dir: &std::path::Path, dest_dir: &std::path::Path) -> bool {
-        if let Err(e) = fs::create_dir_all(dest_dir) {
-            eprintln!("Error creando carpeta de destino {:?}: {}", dest_dir, e);
-            return false;
-        }
-
-        let mut success = true;
-        for entry in WalkDir::new(src_dir).follow_links(false).into_iter() {
-            let entry = match entry {
-                Ok(e) => e,
-                Err(e) => {
-                    eprintln!("Error recorriendo carpeta {:?}: {}", src_dir, e);
-                    success = false;
-                    continue;
-                }
-            };
-
-            let src_path = entry.path();
-            if src_path == src_dir {
-                continue;
-            }
-
-            let rel = match src_path.strip_prefix(src_dir) {
-                Ok(r) => r,
-                Err(e) => {
-                    eprintln!("Error calculando ruta relativa {:?}: {}", src_path, e);
-                    success = false;
-                    continue;
-                }
-            };
-
-            let dest_path = dest_dir.join(rel);
-
-            if entry.file_type().is_dir() {
-                if let Err(e) = fs::create_dir_all(&dest_path) {
-                    eprintln!("Error creando subcarpeta {:?}: {}", dest_path, e);
-                    success = false;
-                }
-            } else {
-                if let Some(parent) = dest_path.parent() {
-                    if let Err(e) = fs::create_dir_all(parent) {
-                        eprintln!("Error creando carpeta padre {:?}: {}", parent, e);
-                        success = false;
-                        continue;
-                    }
-                }
-                if let Err(e) = fs::copy(src_path, &dest_path) {
-                    eprintln!(
-                        "Error copiando archivo {:?} a {:?}: {}",
-                        src_path, dest_path, e
-                    );
-                    success = false;
-                }
-            }
-        }
-        success
-    }
-
-    let mut copied_any = false;
-    let mut last_error: Option<String> = None;
-    let mut skipped_same_dir = false;
-
-    for p in paths {
-        let src = PathBuf::from(p);
-        if !src.is_absolute() {
-            continue;
-        }
-        if !src.exists() {
-            continue;
-        }
-
-        if let Some(parent) = src.parent() {
-            if parent == dest_dir {
-                skipped_same_dir = true;
-                continue;
-            }
-        }
-
-        let file_name = match src.file_name().and_then(|n| n.to_str()) {
-            Some(n) if !n.is_empty() => n.to_string(),
-            _ => continue,
-        };
-
-        if src.is_dir() {
-            let dest = unique_dest_path(&dest_dir, &file_name, true);
-
-            let mut moved = false;
-            if !copy_mode {
-                match fs::rename(&src, &dest) {
-                    Ok(_) => {
-                        copied_any = true;
-                        moved = true;
-                    }
-                    Err(e) => {
-                        println!("No se pudo renombrar carpeta (posiblemente entre discos): {}, intentando copia recursiva y borrado", e);
-                    }
-                }
-            }
-
-            if !moved {
-                if copy_dir_recursive(&src, &dest) {
-                    copied_any = true;
-                    if !copy_mode {
-                        if let Err(e) = fs::remove_dir_all(&src) {
-                            eprintln!("Error borrando carpeta original {:?}: {}", src, e);
-                            last_error = Some(format!(
-                                "Se copió pero no se pudo borrar la carpeta original: {}",
-                                e
-                            ));
-                        }
-                    }
-                } else {
-                    last_error = Some(format!("Error al copiar carpeta {:?}", src));
-                }
-            }
-        } else {
-            let dest = unique_dest_path(&dest_dir, &file_name, false);
-
-            let mut moved = false;
-            if !copy_mode {
-                match fs::rename(&src, &dest) {
-                    Ok(_) => {
-                        copied_any = true;
-                        moved = true;
-                    }
-                    Err(e) => {
-                        println!("No se pudo renombrar archivo (posiblemente entre discos): {}, intentando copiar y borrar", e);
-                    }
-                }
-            }
-
-            if !moved {
-                match fs::copy(&src, &dest) {
-                    Ok(_) => {
-                        copied_any = true;
-                        if !copy_mode {
-                            if let Err(e) = fs::remove_file(&src) {
-                                eprintln!("Error borrando archivo original {:?}: {}", src, e);
-                                last_error = Some(format!(
-                                    "Se copió pero no se pudo borrar el original: {}",
-                                    e
-                                ));
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Error copiando archivo {:?} a {:?}: {}", src, dest, e);
-                        last_error = Some(format!("No se pudo copiar {:?}: {}", src, e));
-                    }
-                }
-            }
-        }
-    }
-
-    if copied_any {
-        Ok(())
-    } else if skipped_same_dir && last_error.is_none() {
-        Ok(())
-    } else {
-        Err(last_error
-            .unwrap_or_else(|| "No se pudieron importar los archivos arrastrados".to_string()))
-    }
+    conflict: Option<ConflictPolicy>,
+) -> Result<TransferReport, ArchiError> {
+    transfer::import_dropped(app, &folder, "Biblioteca", paths, copy_mode, conflict).await
 }
 
 #[tauri::command]
-pub fn import_dropped_resources(
+pub async fn import_dropped_resources(
+    app: AppHandle,
     folder: String,
     paths: Vec<String>,
     copy_mode: bool,
-) -> Result<(), String> {
-    println!("Importing resources. Copy mode: {}", copy_mode);
-    let dest_dir = resolve_path(&folder, "Recursos");
-    if !dest_dir.exists() || !dest_dir.is_dir() {
-        return Err("Carpeta destino no encontrada".to_string());
-    }
-
-    fn unique_dest_path(dest_dir: &std::path::Path, base_name: &str, is_dir: bool) -> PathBuf {
-        let initial = dest_dir.join(base_name);
-        if !initial.exists() {
-            return initial;
-        }
-
-        let (stem, ext) = if !is_dir {
-            match base_name.rsplit_once('.') {
-                Some((s, e)) if !s.is_empty() && !e.is_empty() => {
-                    (s.to_string(), Some(e.to_string()))
-                }
-                _ => (base_name.to_string(), None),
-            }
-        } else {
-            (base_name.to_string(), None)
-        };
-
-        for n in 2..10_000 {
-            let candidate_name = match &ext {
-                Some(e) => format!("{} ({}).{}", stem, n, e),
-                None => format!("{} ({})", stem, n),
-            };
-            let candidate = dest_dir.join(candidate_name);
-            if !candidate.exists() {
-                return candidate;
-            }
-        }
-
-        dest_dir.join(base_name)
-    }
-
-    fn copy_dir_recursive(src_dir: &std::path::Path, dest_dir: &std::path::Path) -> bool {
-        if let Err(e) = fs::create_dir_all(dest_dir) {
-            eprintln!("Error creando carpeta de destino {:?}: {}", dest_dir, e);
-            return false;
-        }
-
-        let mut success = true;
-        for entry in WalkDir::new(src_dir).follow_links(false).into_iter() {
-            let entry = match entry {
-                Ok(e) => e,
-                Err(e) => {
-                    eprintln!("Error recorriendo carpeta {:?}: {}", src_dir, e);
-                    success = false;
-                    continue;
-                }
-            };
-
-            let src_path = entry.path();
-            if src_path == src_dir {
-                continue;
-            }
-
-            let rel = match src_path.strip_prefix(src_dir) {
-                Ok(r) => r,
-                Err(e) => {
-                    eprintln!("Error calculando ruta relativa {:?}: {}", src_path, e);
-                    success = false;
-                    continue;
-                }
-            };
-
-            let dest_path = dest_dir.join(rel);
-
-            if entry.file_type().is_dir() {
-                if let Err(e) = fs::create_dir_all(&dest_path) {
-                    eprintln!("Error creando subcarpeta {:?}: {}", dest_path, e);
-                    success = false;
-                }
-            } else {
-                if let Some(parent) = dest_path.parent() {
-                    if let Err(e) = fs::create_dir_all(parent) {
-                        eprintln!("Error creando carpeta padre {:?}: {}", parent, e);
-                        success = false;
-                        continue;
-                    }
-                }
-                if let Err(e) = fs::copy(src_path, &dest_path) {
-                    eprintln!(
-                        "Error copiando archivo {:?} a {:?}: {}",
-                        src_path, dest_path, e
-                    );
-                    success = false;
-                }
-            }
-        }
-        success
-    }
-
-    let mut copied_any = false;
-    let mut last_error: Option<String> = None;
-    let mut skipped_same_dir = false;
-
-    for p in paths {
-        let src = PathBuf::from(p);
-        if !src.is_absolute() {
-            continue;
-        }
-        if !src.exists() {
-            continue;
-        }
-
-        if let Some(parent) = src.parent() {
-            if parent == dest_dir {
-                skipped_same_dir = true;
-                continue;
-            }
-        }
-
-        let file_name = match src.file_name().and_then(|n| n.to_str()) {
-            Some(n) if !n.is_empty() => n.to_string(),
-            _ => continue,
-        };
-
-        if src.is_dir() {
-            let dest = unique_dest_path(&dest_dir, &file_name, true);
-
-            let mut moved = false;
-            if !copy_mode {
-                match fs::rename(&src, &dest) {
-                    Ok(_) => {
-                        copied_any = true;
-                        moved = true;
-                    }
-                    Err(e) => {
-                        println!("No se pudo renombrar carpeta (posiblemente entre discos): {}, intentando copia recursiva y borrado", e);
-                    }
-                }
-            }
-
-            if !moved {
-                if copy_dir_recursive(&src, &dest) {
-                    copied_any = true;
-                    if !copy_mode {
-                        if let Err(e) = fs::remove_dir_all(&src) {
-                            eprintln!("Error borrando carpeta original {:?}: {}", src, e);
-                            last_error = Some(format!(
-                                "Se copió pero no se pudo borrar la carpeta original: {}",
-                                e
-                            ));
-                        }
-                    }
-                } else {
-                    last_error = Some(format!("Error al copiar carpeta {:?}", src));
-                }
-            }
-        } else {
-            let dest = unique_dest_path(&dest_dir, &file_name, false);
-
-            let mut moved = false;
-            if !copy_mode {
-                match fs::rename(&src, &dest) {
-                    Ok(_) => {
-                        copied_any = true;
-                        moved = true;
-                    }
-                    Err(e) => {
-                        println!("No se pudo renombrar archivo (posiblemente entre discos): {}, intentando copiar y borrar", e);
-                    }
-                }
-            }
-
-            if !moved {
-                match fs::copy(&src, &dest) {
-                    Ok(_) => {
-                        copied_any = true;
-                        if !copy_mode {
-                            if let Err(e) = fs::remove_file(&src) {
-                                eprintln!("Error borrando archivo original {:?}: {}", src, e);
-                                last_error = Some(format!(
-                                    "Se copió pero no se pudo borrar el original: {}",
-                                    e
-                                ));
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Error copiando archivo {:?} a {:?}: {}", src, dest, e);
-                        last_error = Some(format!("No se pudo copiar {:?}: {}", src, e));
-                    }
-                }
-            }
-        }
-    }
-
-    if copied_any {
-        Ok(())
-    } else if skipped_same_dir && last_error.is_none() {
-        Ok(())
-    } else {
-        Err(last_error
-            .unwrap_or_else(|| "No se pudieron importar los archivos arrastrados".to_string()))
-    }
+    conflict: Option<ConflictPolicy>,
+) -> Result<TransferReport, ArchiError> {
+    transfer::import_dropped(app, &folder, "Recursos", paths, copy_mode, conflict).await
 }
 
 // ==========================================
@@ -1014,11 +553,11 @@ pub fn import_dropped_resources(
 // ==========================================
 
 #[tauri::command]
-pub fn list_resources(folder: String) -> Result<Vec<FileItem>, String> {
-    let folder_path = resolve_path(&folder, "Recursos");
+pub fn list_resources(folder: String) -> Result<Vec<FileItem>, ArchiError> {
+    let folder_path = resolve_path(&folder, "Recursos")?;
 
     if !folder_path.exists() || !folder_path.is_dir() {
-        return Err("Carpeta no encontrada".to_string());
+        return Err(ArchiError::NotFound(folder_path));
     }
 
     let mut files = Vec::new();
@@ -1076,49 +615,62 @@ pub fn list_resources(folder: String) -> Result<Vec<FileItem>, String> {
 }
 
 #[tauri::command]
-pub fn create_resource(parent: String, name: String) -> Result<(), String> {
-    let target_base = resolve_path(&parent, "Recursos");
-    let target_path = target_base.join(&name);
+pub fn create_resource(parent: String, name: String) -> Result<(), ArchiError> {
+    let target_base = resolve_path(&parent, "Recursos")?;
+    let target_path = target_base.join(validate_name(&name)?);
 
     if target_path.exists() {
-        return Err("Ya existe un recurso con ese nombre".to_string());
+        return Err(ArchiError::AlreadyExists(target_path));
     }
 
-    fs::create_dir_all(&target_path).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&target_path).map_err(|e| ArchiError::io(&target_path, e))?;
+    journal::record(Operation::Create { path: target_path });
     Ok(())
 }
 
 #[tauri::command]
-pub fn rename_resource(parent: String, old_name: String, new_name: String) -> Result<(), String> {
-    let base_path = resolve_path(&parent, "Recursos");
-    let old_path = base_path.join(&old_name);
+pub fn rename_resource(
+    parent: String,
+    old_name: String,
+    new_name: String,
+) -> Result<(), ArchiError> {
+    let new_name = validate_name(&new_name)?;
+    let base_path = resolve_path(&parent, "Recursos")?;
+    let old_path = child_path(&base_path, &old_name)?;
     let new_path = base_path.join(&new_name);
 
     if !old_path.exists() {
-        return Err("El recurso original no existe".to_string());
+        return Err(ArchiError::NotFound(old_path));
     }
     if new_path.exists() && old_name.to_lowercase() != new_name.to_lowercase() {
-        return Err("Ya existe un recurso con el nuevo nombre".to_string());
+        return Err(ArchiError::AlreadyExists(new_path));
     }
 
-    fs::rename(old_path, new_path).map_err(|e| e.to_string())?;
+    fs::rename(&old_path, &new_path).map_err(|e| ArchiError::io(&new_path, e))?;
 
     // Actualizar layout en metadatos si existe
     let _ = update_metadata_layout_on_rename(&base_path, &old_name, &new_name);
+    journal::record(Operation::Rename {
+        from: old_path,
+        to: new_path,
+    });
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_resource(parent: String, name: String) -> Result<(), String> {
-    let base_path = resolve_path(&parent, "Recursos");
-    let target_path = base_path.join(&name);
+pub fn delete_resource(parent: String, name: String) -> Result<(), ArchiError> {
+    let base_path = resolve_path(&parent, "Recursos")?;
+    let target_path = child_path(&base_path, &name)?;
 
     if !target_path.exists() {
-        return Err("El recurso no existe".to_string());
+        return Err(ArchiError::NotFound(target_path));
     }
 
-    trash::delete(target_path).map_err(|e| e.to_string())?;
+    trash_bin::delete(std::slice::from_ref(&target_path))?;
+    journal::record(Operation::Trash {
+        paths: vec![target_path],
+    });
     Ok(())
 }
 
@@ -1246,27 +798,14 @@ fn extract_rtf_text(rtf_content: &str) -> String {
 }
 
 #[tauri::command]
-pub fn read_file_preview(path: String) -> Result<serde_json::Value, String> {
+pub fn read_file_preview(path: String) -> Result<serde_json::Value, ArchiError> {
     let file_path = PathBuf::from(&path);
 
-    // Validar seguridad básica
-    let user_data_dir = get_user_data_dir();
-    if !file_path.starts_with(&user_data_dir) {
-        // Permitir acceso solo si está dentro de user_data_dir (o subdirectorios permitidos)
-        // Nota: Esto es estricto. Si se requiere acceso fuera, ajustar.
-        // Dado el contexto de "Recursos" y "Biblioteca", suele ser dentro de user_data_dir.
-        // Pero para estar seguros y no romper nada si el usuario tiene symlinks o rutas raras:
-        // Si no empieza con user_data_dir, chequeamos si existe y es un archivo regular.
-        // Para máxima seguridad según instrucciones previas:
-        // if !file_path.starts_with(user_data_dir) { return Err("Acceso denegado".to_string()); }
-        // Pero mantendremos el comportamiento previo si no estamos seguros de la restricción.
-        // El usuario pidió "no tocar cosas extras". Mejor no agrego restricciones de ruta nuevas si no me las pidieron explícitamente AHORA.
-        // Simplemente validamos existencia.
-    }
-
     if !file_path.exists() {
-        return Err("Archivo no encontrado".to_string());
+        return Err(ArchiError::NotFound(file_path));
     }
+    // Solo archivos dentro de la carpeta de ARCHI
+    let file_path = library_path(&path)?;
 
     let is_rtf = path.to_lowercase().ends_with(".rtf");
     let limit = if is_rtf { 16384 } else { 4096 }; // Leer más bytes para RTF por la cabecera
@@ -1299,29 +838,20 @@ pub fn open_in_finder(
     folder: Option<String>,
     item: Option<String>,
     subfolder: Option<String>,
-) -> Result<(), String> {
-    let user_data_dir = get_user_data_dir();
-    let mut path = user_data_dir;
+) -> Result<(), ArchiError> {
+    let mut path = get_user_data_dir();
 
     if let Some(f) = folder {
         let sub = subfolder.unwrap_or_else(|| "Biblioteca".to_string());
-        let parts: Vec<&str> = f.split('/').collect();
-        if parts.len() > 1 {
-            path = path.join(parts[0]).join(&sub);
-            for part in &parts[1..] {
-                path = path.join(part);
-            }
-        } else {
-            path = path.join(&f).join(&sub);
-        }
+        path = resolve_path(&f, &sub)?;
     }
 
     if let Some(ref i) = item {
-        path = path.join(i);
+        path = child_path(&path, i)?;
     }
 
     if !path.exists() {
-        return Err("Path not found".to_string());
+        return Err(ArchiError::NotFound(path));
     }
 
     #[cfg(target_os = "macos")]
@@ -1347,11 +877,11 @@ pub fn open_in_finder(
 }
 
 #[tauri::command]
-pub fn open_config() -> Result<(), String> {
+pub fn open_config() -> Result<(), ArchiError> {
     let config_path = get_config_path();
 
     if !config_path.exists() {
-        return Err("Archivo de configuración no encontrado".to_string());
+        return Err(ArchiError::NotFound(config_path));
     }
 
     #[cfg(target_os = "macos")]
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use tauri::AppHandle;
+
+use crate::error::ArchiError;
+use crate::filesystem::update_metadata_layout_on_rename;
+use crate::metadata_store;
+use crate::models::{FileLayout, TransferReport, TransferStatus};
+use crate::transfer::{self, TransferMode};
+use crate::trash_bin;
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+/// Operaciones que se recuerdan para deshacer.
+const MAX_ENTRIES: usize = 100;
+
+// ==========================================
+// MODELOS
+// ==========================================
+
+type Layout = Option<HashMap<String, FileLayout>>;
+
+/// Cambio hecho por un comando, con lo necesario para revertirlo.
+#[derive(Debug, Clone)]
+pub(crate) enum Operation {
+    /// Carpeta creada (cliente, carpeta de trabajo o de recursos).
+    Create {
+        path: PathBuf,
+    },
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// Elementos enviados a la papelera del sistema.
+    Trash {
+        paths: Vec<PathBuf>,
+    },
+    /// Elementos importados, copiados o movidos, como pares (origen, destino).
+    /// `replaced` son los destinos cuyo contenido anterior se sustituyó y
+    /// está en la papelera.
+    Transfer {
+        mode: TransferMode,
+        entries: Vec<(PathBuf, PathBuf)>,
+        replaced: Vec<PathBuf>,
+    },
+    /// Posiciones de la cuadrícula de `dir` antes y después de guardarlas.
+    Layout {
+        dir: PathBuf,
+        before: Layout,
+        after: Layout,
+    },
+}
+
+impl Operation {
+    /// Descripción para la interfaz ("Deshacer ...").
+    fn label(&self) -> &'static str {
+        match self {
+            Operation::Create { .. } => "Crear carpeta",
+            Operation::Rename { .. } => "Renombrar",
+            Operation::Trash { .. } => "Mover a la papelera",
+            Operation::Transfer {
+                mode: TransferMode::Copy,
+                ..
+            } => "Copiar",
+            Operation::Transfer {
+                mode: TransferMode::Move,
+                ..
+            } => "Mover",
+            Operation::Layout { .. } => "Ordenar",
+        }
+    }
+}
+
+#[derive(Default)]
+struct Journal {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+}
+
+fn journal() -> MutexGuard<'static, Journal> {
+    static JOURNAL: OnceLock<Mutex<Journal>> = OnceLock::new();
+
+    JOURNAL
+        .get_or_init(|| Mutex::new(Journal::default()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+// ==========================================
+// REGISTRO
+// ==========================================
+
+/// Anota una operación ya hecha. Descarta lo que hubiera para rehacer.
+pub(crate) fn record(operation: Operation) {
+    let mut journal = journal();
+    journal.undo.push(operation);
+    journal.redo.clear();
+    if journal.undo.len() > MAX_ENTRIES {
+        journal.undo.remove(0);
+    }
+}
+
+/// Anota los elementos que una transferencia completó.
+pub(crate) fn record_transfer(mode: TransferMode, report: &TransferReport) {
+    let mut entries = Vec::new();
+    let mut replaced = Vec::new();
+    for item in &report.items {
+        let Some(destination) = item.destination.as_ref() else {
+            continue;
+        };
+        if item.status != TransferStatus::Done {
+            continue;
+        }
+        let destination = PathBuf::from(destination);
+        if item.replaced {
+            replaced.push(destination.clone());
+        }
+        entries.push((PathBuf::from(&item.source), destination));
+    }
+
+    if !entries.is_empty() {
+        record(Operation::Transfer {
+            mode,
+            entries,
+            replaced,
+        });
+    }
+}
+
+// ==========================================
+// DESHACER Y REHACER
+// ==========================================
+
+fn rename(from: &Path, to: &Path) -> Result<(), ArchiError> {
+    let case_only = from.to_string_lossy().to_lowercase() == to.to_string_lossy().to_lowercase();
+    if to.exists() && !case_only {
+        return Err(ArchiError::AlreadyExists(to.to_path_buf()));
+    }
+    fs::rename(from, to).map_err(|e| ArchiError::io(from, e))?;
+
+    if let (Some(base), Some(old_name), Some(new_name)) =
+        (to.parent(), from.file_name(), to.file_name())
+    {
+        if from.parent() == Some(base) {
+            let _ = update_metadata_layout_on_rename(
+                base,
+                &old_name.to_string_lossy(),
+                &new_name.to_string_lossy(),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn set_layout(dir: &Path, layout: &Layout) -> Result<(), ArchiError> {
+    metadata_store::update(dir, |meta| meta.layout = layout.clone())?;
+    Ok(())
+}
+
+fn revert(app: &AppHandle, operation: &Operation) -> Result<(), ArchiError> {
+    match operation {
+        Operation::Create { path } => trash_bin::delete(std::slice::from_ref(path)),
+        Operation::Rename { from, to } => rename(to, from),
+        Operation::Trash { paths } => trash_bin::restore(paths),
+        Operation::Transfer {
+            mode: TransferMode::Copy,
+            entries,
+            replaced,
+        } => {
+            // Las copias que sustituyeron algo se borran sin pasar por la
+            // papelera: lo sustituido está allí con la misma ruta original
+            let (overwrites, copies): (Vec<PathBuf>, Vec<PathBuf>) = entries
+                .iter()
+                .map(|(_, dest)| dest.clone())
+                .partition(|dest| replaced.contains(dest));
+            trash_bin::delete(&copies)?;
+            for dest in &overwrites {
+                transfer::remove_path(dest).map_err(|e| ArchiError::io(dest, e))?;
+            }
+            trash_bin::restore(replaced)
+        }
+        Operation::Transfer {
+            mode: TransferMode::Move,
+            entries,
+            replaced,
+        } => {
+            entries.iter().try_for_each(|(src, dest)| {
+                transfer::transfer_path(app, dest, src, TransferMode::Move)
+            })?;
+            trash_bin::restore(replaced)
+        }
+        Operation::Layout { dir, before, .. } => set_layout(dir, before),
+    }
+}
+
+fn reapply(app: &AppHandle, operation: &Operation) -> Result<(), ArchiError> {
+    match operation {
+        // Se recupera de la papelera para no perder lo que tuviera dentro
+        Operation::Create { path } => trash_bin::restore(std::slice::from_ref(path)),
+        Operation::Rename { from, to } => rename(from, to),
+        Operation::Trash { paths } => trash_bin::delete(paths),
+        Operation::Transfer {
+            mode: TransferMode::Copy,
+            entries,
+            replaced,
+        } => {
+            let (overwrites, copies): (Vec<_>, Vec<_>) = entries
+                .iter()
+                .partition(|(_, dest)| replaced.contains(dest));
+            let copies: Vec<PathBuf> = copies.into_iter().map(|(_, dest)| dest.clone()).collect();
+            trash_bin::restore(&copies)?;
+            // Las que sustituían algo se borraron al deshacer: se copian de nuevo
+            trash_bin::delete(replaced)?;
+            overwrites.into_iter().try_for_each(|(src, dest)| {
+                transfer::transfer_path(app, src, dest, TransferMode::Copy)
+            })
+        }
+        Operation::Transfer {
+            mode: TransferMode::Move,
+            entries,
+            replaced,
+        } => {
+            trash_bin::delete(replaced)?;
+            entries.iter().try_for_each(|(src, dest)| {
+                transfer::transfer_path(app, src, dest, TransferMode::Move)
+            })
+        }
+        Operation::Layout { dir, after, .. } => set_layout(dir, after),
+    }
+}
+
+// ==========================================
+// COMANDOS
+// ==========================================
+
+/// Deshace la última operación y devuelve su descripción (`None` si no hay
+/// nada que deshacer). Si falla, la operación se descarta: el disco ya no
+/// está como ella lo dejó.
+#[tauri::command]
+pub async fn undo_last(app: AppHandle) -> Result<Option<String>, ArchiError> {
+    let Some(operation) = journal().undo.pop() else {
+        return Ok(None);
+    };
+
+    let operation =
+        tauri::async_runtime::spawn_blocking(move || revert(&app, &operation).map(|_| operation))
+            .await
+            .map_err(|e| e.to_string())??;
+
+    let label = operation.label().to_string();
+    journal().redo.push(operation);
+    Ok(Some(label))
+}
+
+/// Rehace la última operación deshecha y devuelve su descripción.
+#[tauri::command]
+pub async fn redo(app: AppHandle) -> Result<Option<String>, ArchiError> {
+    let Some(operation) = journal().redo.pop() else {
+        return Ok(None);
+    };
+
+    let operation =
+        tauri::async_runtime::spawn_blocking(move || reapply(&app, &operation).map(|_| operation))
+            .await
+            .map_err(|e| e.to_string())??;
+
+    let label = operation.label().to_string();
+    journal().undo.push(operation);
+    Ok(Some(label))
+}
@@ -1,11 +1,27 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 
+mod cache_index;
+mod duplicates;
+mod error;
+mod file_details;
 mod filesystem;
+mod journal;
+mod libraries;
 mod menu;
+mod metadata_store;
+mod migrations;
 mod models;
+mod palette;
 mod paths;
 mod setup_directories;
+mod similarity;
+mod thumbnail_backends;
 mod thumbnails;
+mod tiff;
+mod transfer;
+mod trash_bin;
+mod validation;
+mod watcher;
 
 #[tauri::command]
 fn show_main_window(window: tauri::Window) {
@@ -17,9 +33,21 @@ fn show_main_window(window: tauri::Window) {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .register_asynchronous_uri_scheme_protocol(
+            thumbnails::THUMBNAIL_SCHEME,
+            |_ctx, request, responder| {
+                // Generar una miniatura puede tardar: fuera del hilo del webview
+                tauri::async_runtime::spawn_blocking(move || {
+                    responder.respond(thumbnails::handle_thumbnail_request(&request));
+                });
+            },
+        )
         .manage(thumbnails::CacheCancellation {
             canceled: std::sync::atomic::AtomicBool::new(false),
+            running: std::sync::Mutex::new(()),
         })
+        .manage(watcher::FsWatcher::default())
+        .manage(transfer::TransferJobs::default())
         .setup(|app| {
             use tauri::Manager;
 
@@ -60,6 +88,9 @@ pub fn run() {
                 eprintln!("Error en setup inicial: {}", e);
             }
 
+            // Limpieza de caché en segundo plano, una vez por arranque
+            thumbnails::cache_cleanup();
+
             // Intentar aplicar resolución guardada al iniciar
             if let Some(window) = app.get_webview_window("main") {
                 let config = paths::get_config();
@@ -109,6 +140,10 @@ pub fn run() {
                 let _ = app.emit("nav-left", ());
             } else if id.as_ref() == "nav_right" {
                 let _ = app.emit("nav-right", ());
+            } else if id.as_ref() == "edit_undo" {
+                let _ = app.emit("history-command", "undo");
+            } else if id.as_ref() == "edit_redo" {
+                let _ = app.emit("history-command", "redo");
             } else if id.as_ref() == "edit_paste_plain" {
                 let _ = app.emit("finder-note-command", "pastePlain");
             } else if id.as_ref() == "edit_bold" {
@@ -140,6 +175,11 @@ pub fn run() {
             paths::get_default_config,
             // Instalación/Setup
             setup_directories::ensure_user_setup,
+            // Bibliotecas
+            libraries::list_libraries,
+            libraries::add_library,
+            libraries::switch_library,
+            libraries::remove_library,
             // Sistema de Archivos - Clientes
             filesystem::list_clients,
             filesystem::create_client,
@@ -156,6 +196,16 @@ pub fn run() {
             filesystem::save_work_layout,
             filesystem::import_dropped_items,
             filesystem::import_dropped_resources,
+            transfer::move_items,
+            transfer::copy_items,
+            transfer::cancel_transfer,
+            // Historial
+            journal::undo_last,
+            journal::redo,
+            // Papelera
+            trash_bin::list_trashed_items,
+            trash_bin::restore_trashed_items,
+            trash_bin::purge_trashed_items,
             // Sistema de Archivos - Recursos
             filesystem::list_resources,
             filesystem::create_resource,
@@ -172,6 +222,16 @@ pub fn run() {
             thumbnails::open_cache_folder,
             thumbnails::generate_missing_thumbnails,
             thumbnails::cancel_cache_generation,
+            // Análisis de archivos
+            file_details::get_file_details,
+            palette::extract_palette,
+            palette::find_by_color,
+            similarity::find_similar,
+            similarity::find_similar_groups,
+            duplicates::scan_duplicates,
+            duplicates::trash_duplicates,
+            // Vigilancia de carpetas
+            watcher::watch_client,
             // Sistema
             filesystem::open_config,
             show_main_window
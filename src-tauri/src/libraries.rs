@@ -0,0 +1,156 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::cache_index;
+use crate::error::ArchiError;
+use crate::models::{AppConfig, Library, LibraryRoot};
+use crate::paths::{
+    find_library, get_config, get_library_cache_dir, get_user_data_dir, save_config,
+    set_active_library,
+};
+use crate::setup_directories::ensure_user_setup;
+use crate::thumbnails::{self, CacheCancellation};
+use crate::validation::validate_name;
+use crate::watcher::FsWatcher;
+
+// ==========================================
+// FUNCIONES AUXILIARES
+// ==========================================
+
+fn to_library(root: &LibraryRoot, active: &Path) -> Library {
+    let path = Path::new(&root.path);
+    Library {
+        name: root.name.clone(),
+        path: root.path.clone(),
+        active: path == active,
+        available: path.is_dir(),
+    }
+}
+
+/// Biblioteca registrada en `path`, o error si no lo está.
+fn registered(config: &AppConfig, path: &str) -> Result<LibraryRoot, ArchiError> {
+    find_library(config, Path::new(path))
+        .ok_or_else(|| ArchiError::Other(format!("La biblioteca \"{}\" no está registrada", path)))
+}
+
+// ==========================================
+// COMANDOS
+// ==========================================
+
+#[tauri::command]
+pub fn list_libraries() -> Vec<Library> {
+    let active = get_user_data_dir();
+    get_config()
+        .library_roots
+        .iter()
+        .map(|root| to_library(root, &active))
+        .collect()
+}
+
+/// Registra como biblioteca una carpeta existente (disco externo, NAS...).
+/// Sin `name` se usa el nombre de la carpeta. No cambia la biblioteca activa.
+#[tauri::command]
+pub fn add_library(path: String, name: Option<String>) -> Result<Library, ArchiError> {
+    let root = PathBuf::from(&path);
+    if !root.is_absolute() {
+        return Err(ArchiError::Other(format!(
+            "La ruta \"{}\" debe ser absoluta",
+            path
+        )));
+    }
+    let root = root.canonicalize().map_err(|e| ArchiError::io(&root, e))?;
+    if !root.is_dir() {
+        return Err(ArchiError::NotFound(root));
+    }
+
+    let name = match name {
+        Some(name) => validate_name(&name)?,
+        None => validate_name(&root.file_name().unwrap_or_default().to_string_lossy())?,
+    };
+
+    let mut config = get_config();
+    for library in &config.library_roots {
+        let existing = Path::new(&library.path);
+        if library.name.eq_ignore_ascii_case(&name) || existing == root {
+            return Err(ArchiError::AlreadyExists(root));
+        }
+        // Una biblioteca dentro de otra mostraría sus clientes dos veces
+        if existing.starts_with(&root) || root.starts_with(existing) {
+            return Err(ArchiError::Other(format!(
+                "\"{}\" se solapa con la biblioteca \"{}\"",
+                root.display(),
+                library.name
+            )));
+        }
+    }
+
+    let library = LibraryRoot {
+        name,
+        path: root.to_string_lossy().to_string(),
+    };
+    config.library_roots.push(library.clone());
+    save_config(config)?;
+
+    Ok(to_library(&library, &get_user_data_dir()))
+}
+
+/// Abre la biblioteca registrada en `path` y emite `library-changed` para que
+/// la interfaz vuelva a listar los clientes y vigile el cliente abierto.
+#[tauri::command]
+pub fn switch_library(
+    app: AppHandle,
+    state: State<FsWatcher>,
+    cache: State<CacheCancellation>,
+    path: String,
+) -> Result<Library, ArchiError> {
+    let mut config = get_config();
+    let library = registered(&config, &path)?;
+    let root = PathBuf::from(&library.path);
+    if !root.is_dir() {
+        return Err(ArchiError::NotFound(root));
+    }
+
+    // La generación de miniaturas en curso se detiene antes de cambiar: sus
+    // trabajos leen la carpeta de caché cada vez y escribirían en la nueva.
+    // El índice pendiente se escribe en la caché de la biblioteca anterior
+    state.stop();
+    let _generation = cache.stop();
+    cache_index::flush();
+
+    config.active_library = library.path.clone();
+    save_config(config)?;
+    set_active_library(root.clone());
+    cache_index::reload();
+
+    ensure_user_setup()?;
+    // El índice de la nueva biblioteca se sincroniza con sus archivos
+    thumbnails::cache_cleanup();
+    let library = to_library(&library, &root);
+    let _ = app.emit("library-changed", &library);
+    Ok(library)
+}
+
+/// Quita una biblioteca de la lista y borra su caché. Sus archivos no se tocan.
+#[tauri::command]
+pub fn remove_library(path: String) -> Result<(), ArchiError> {
+    let mut config = get_config();
+    let library = registered(&config, &path)?;
+    let root = PathBuf::from(&library.path);
+
+    if root == get_user_data_dir() {
+        return Err(ArchiError::Other(
+            "No se puede quitar la biblioteca activa".to_string(),
+        ));
+    }
+
+    config.library_roots.retain(|l| l.path != library.path);
+    save_config(config)?;
+
+    let cache_dir = get_library_cache_dir(&root);
+    if cache_dir.exists() {
+        fs::remove_dir_all(&cache_dir).map_err(|e| ArchiError::io(&cache_dir, e))?;
+    }
+    Ok(())
+}
@@ -42,8 +42,15 @@ pub fn init<R: Runtime>(app: &App<R>) -> tauri::Result<Menu<R>> {
         "Edición",
         true,
         &[
-            &PredefinedMenuItem::undo(app, Some("Deshacer"))?,
-            &PredefinedMenuItem::redo(app, Some("Rehacer"))?,
+            // Deshacen texto o, fuera de un campo de texto, operaciones de archivos
+            &MenuItem::with_id(app, "edit_undo", "Deshacer", true, Some("CmdOrCtrl+Z"))?,
+            &MenuItem::with_id(
+                app,
+                "edit_redo",
+                "Rehacer",
+                true,
+                Some("CmdOrCtrl+Shift+Z"),
+            )?,
             &PredefinedMenuItem::separator(app)?,
             &PredefinedMenuItem::cut(app, Some("Cortar"))?,
             &PredefinedMenuItem::copy(app, Some("Copiar"))?,
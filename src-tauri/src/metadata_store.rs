@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::migrations::{self, metadata_version, Schema};
+use crate::models::Metadata;
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+const METADATA_FILE: &str = ".metadatos.json";
+
+// ==========================================
+// BLOQUEOS
+// ==========================================
+
+/// Un cerrojo por archivo de metadatos para serializar las escrituras.
+fn file_lock(path: &Path) -> Arc<Mutex<()>> {
+    static LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = OnceLock::new();
+
+    let mut locks = LOCKS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    locks.entry(path.to_path_buf()).or_default().clone()
+}
+
+// ==========================================
+// LECTURA Y ESCRITURA
+// ==========================================
+
+pub(crate) fn metadata_path(dir: &Path) -> PathBuf {
+    dir.join(METADATA_FILE)
+}
+
+fn parse(content: &str) -> Result<Metadata, String> {
+    migrations::parse(content, Schema::Metadata).map(|(meta, _)| meta)
+}
+
+/// Metadatos de `dir`, migrados a la versión actual en memoria (el archivo
+/// se actualiza en la siguiente escritura). Vacíos si no existen o no se
+/// pueden leer.
+pub(crate) fn read(dir: &Path) -> Metadata {
+    fs::read_to_string(metadata_path(dir))
+        .ok()
+        .and_then(|c| parse(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Lee, modifica con `f` y guarda los metadatos de `dir` sin que otra
+/// escritura pueda intercalarse. Si el archivo existente está dañado se
+/// conserva una copia antes de sustituirlo; si no se puede leer, no se toca.
+pub(crate) fn update<T>(dir: &Path, f: impl FnOnce(&mut Metadata) -> T) -> Result<T, String> {
+    let path = metadata_path(dir);
+    let lock = file_lock(&path);
+    let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut meta = match fs::read_to_string(&path) {
+        Ok(content) => match parse(&content) {
+            Ok(meta) => meta,
+            Err(_) => {
+                backup_corrupt(&path)?;
+                Metadata::default()
+            }
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Metadata::default(),
+        // Contenido que no es UTF-8: dañado como un JSON inválido
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            backup_corrupt(&path)?;
+            Metadata::default()
+        }
+        // Cualquier otro fallo (permisos, disco...) no debe borrar lo guardado
+        Err(e) => return Err(format!("No se pudo leer {}: {}", path.display(), e)),
+    };
+
+    let result = f(&mut meta);
+    // Si lo escribió una versión más nueva se mantiene su número
+    meta.schema_version = meta.schema_version.max(metadata_version());
+    let json = serde_json::to_string_pretty(&meta).map_err(|e| e.to_string())?;
+    write_atomic(&path, json.as_bytes())?;
+    Ok(result)
+}
+
+/// Escribe en un temporal del mismo directorio y lo renombra sobre el
+/// destino, así un corte a mitad nunca deja el archivo a medias.
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let tmp_path = path.with_file_name(format!(
+        "{}.tmp-{}-{}",
+        path.file_name().unwrap_or_default().to_string_lossy(),
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let write = || -> std::io::Result<()> {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    };
+    write().map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        e.to_string()
+    })
+}
+
+/// Renombra un archivo ilegible a `<nombre>.corrupto-<segundos>` (por
+/// ejemplo `.metadatos.json.corrupto-1700000000`).
+pub(crate) fn backup_corrupt(path: &Path) -> Result<(), String> {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let backup = path.with_file_name(format!(
+        "{}.corrupto-{}",
+        path.file_name().unwrap_or_default().to_string_lossy(),
+        secs
+    ));
+
+    eprintln!(
+        "Archivo dañado: {}, copia guardada en {}",
+        path.display(),
+        backup.display()
+    );
+    fs::rename(path, backup).map_err(|e| e.to_string())
+}
@@ -0,0 +1,88 @@
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+// ==========================================
+// ESQUEMAS VERSIONADOS
+// ==========================================
+
+/// Paso de migración: convierte el JSON de la versión `n` a la `n + 1`.
+type Migration = fn(&mut Map<String, Value>);
+
+/// Archivos JSON de ARCHI con `schema_version`.
+#[derive(Debug, Clone, Copy)]
+pub enum Schema {
+    /// `.metadatos.json` de clientes y carpetas de trabajo.
+    Metadata,
+    /// `config.json`.
+    Config,
+}
+
+impl Schema {
+    /// Migraciones registradas; la posición `n` lleva de la versión `n` a la `n + 1`.
+    fn migrations(self) -> &'static [Migration] {
+        match self {
+            Schema::Metadata => &[metadata_v0_to_v1],
+            Schema::Config => &[config_v0_to_v1],
+        }
+    }
+
+    /// Versión que escribe esta build.
+    pub fn current_version(self) -> u32 {
+        self.migrations().len() as u32
+    }
+}
+
+pub fn metadata_version() -> u32 {
+    Schema::Metadata.current_version()
+}
+
+pub fn config_version() -> u32 {
+    Schema::Config.current_version()
+}
+
+// ==========================================
+// LECTURA CON MIGRACIÓN
+// ==========================================
+
+/// Lee `content` aplicando las migraciones pendientes. Devuelve también si
+/// hubo que migrar (para volver a guardar el archivo). Un archivo de una
+/// versión más nueva se lee tal cual: los campos desconocidos se conservan.
+pub fn parse<T: DeserializeOwned>(content: &str, schema: Schema) -> Result<(T, bool), String> {
+    let mut value: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| "Se esperaba un objeto JSON".to_string())?;
+
+    let version = object
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as u32;
+
+    let pending = schema.migrations().get(version as usize..).unwrap_or(&[]);
+    for (step, migration) in pending.iter().enumerate() {
+        migration(object);
+        object.insert(
+            "schema_version".to_string(),
+            (version + step as u32 + 1).into(),
+        );
+    }
+
+    let parsed = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    Ok((parsed, !pending.is_empty()))
+}
+
+// ==========================================
+// MIGRACIONES
+// ==========================================
+
+/// v0 → v1: archivos anteriores al versionado. El formato no cambia.
+fn metadata_v0_to_v1(_meta: &mut Map<String, Value>) {}
+
+/// v0 → v1: `thumbnail_mode` se normaliza a minúsculas (`fit`/`fill`), que
+/// antes solo se hacía al leerlo.
+fn config_v0_to_v1(config: &mut Map<String, Value>) {
+    if let Some(mode) = config.get("thumbnail_mode").and_then(Value::as_str) {
+        let mode = mode.to_lowercase();
+        config.insert("thumbnail_mode".to_string(), mode.into());
+    }
+}
@@ -1,4 +1,9 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::ArchiError;
+use crate::migrations::config_version;
+use crate::paths::get_default_library_dir;
 
 // ==========================================
 // MODELOS DE CONFIGURACIÓN
@@ -12,6 +17,9 @@ pub struct Resolution {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
+    /// Versión del formato de `config.json` (ver `migrations.rs`).
+    #[serde(default)]
+    pub schema_version: u32,
     pub primary_color: String,
     pub border_color: String,
     pub accent_color: String,
@@ -24,15 +32,50 @@ pub struct AppConfig {
     pub resolution: Resolution,
     #[serde(default = "default_thumbnail_mode")]
     pub thumbnail_mode: String,
+    /// Tamaño máximo de la caché de miniaturas en MB (0 = sin límite).
+    #[serde(default = "default_cache_max_size_mb")]
+    pub cache_max_size_mb: u64,
+    /// Bibliotecas registradas (carpetas raíz con clientes).
+    #[serde(default = "default_library_roots")]
+    pub library_roots: Vec<LibraryRoot>,
+    /// Ruta de la biblioteca abierta; debe estar en `library_roots`.
+    #[serde(default = "default_active_library")]
+    pub active_library: String,
+    /// Claves que esta versión no conoce, conservadas al reescribir.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 fn default_thumbnail_mode() -> String {
     "fit".to_string()
 }
 
+fn default_cache_max_size_mb() -> u64 {
+    1024
+}
+
+fn default_library_roots() -> Vec<LibraryRoot> {
+    vec![LibraryRoot {
+        name: "ARCHI".to_string(),
+        path: default_active_library(),
+    }]
+}
+
+fn default_active_library() -> String {
+    get_default_library_dir().to_string_lossy().to_string()
+}
+
+/// Carpeta raíz de una biblioteca: local, en un disco externo o en un NAS.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LibraryRoot {
+    pub name: String,
+    pub path: String,
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            schema_version: config_version(),
             primary_color: "#111b23".to_string(),
             border_color: "rgba(124, 124, 124, 0.33)".to_string(),
             accent_color: "#2fa5a2".to_string(),
@@ -47,6 +90,10 @@ impl Default for AppConfig {
                 height: 600,
             },
             thumbnail_mode: default_thumbnail_mode(),
+            cache_max_size_mb: default_cache_max_size_mb(),
+            library_roots: default_library_roots(),
+            active_library: default_active_library(),
+            extra: Map::new(),
         }
     }
 }
@@ -59,6 +106,17 @@ impl Default for AppConfig {
 pub struct Client {
     pub name: String,
     pub pinned: bool,
+    /// Nombre de la biblioteca (siempre la activa) que lo contiene.
+    pub library: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Library {
+    pub name: String,
+    pub path: String,
+    pub active: bool,
+    /// `false` si la carpeta no está accesible (disco desconectado, NAS sin montar).
+    pub available: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -74,6 +132,142 @@ pub struct FileItem {
     pub detected_type: String,
 }
 
+/// Datos técnicos de un archivo de imagen o documento, leídos de sus cabeceras.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FileDetails {
+    /// Formato detectado (`jpeg`, `png`, `tiff`, `psd`, `pdf`).
+    pub format: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub dpi_x: Option<f64>,
+    pub dpi_y: Option<f64>,
+    /// `RGB`, `CMYK`, `Gray`, `Indexed`, `Lab`, `Bitmap`...
+    pub color_mode: Option<String>,
+    /// Bits por canal.
+    pub bit_depth: Option<u16>,
+    pub has_icc_profile: bool,
+    /// Tamaño de cada página en puntos (1/72 in), solo PDF.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<PageSize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct PageSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Color de una paleta extraída y fracción de la imagen que ocupa (0..1).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PaletteColor {
+    pub hex: String,
+    pub proportion: f32,
+}
+
+/// Archivo con un color dominante parecido al buscado: ese color de su paleta
+/// y la distancia RGB hasta él (0 = idéntico).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ColorMatch {
+    pub path: String,
+    pub hex: String,
+    pub distance: u32,
+}
+
+/// Archivo parecido a otro y distancia entre sus hashes perceptuales (0 = idénticos).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimilarFile {
+    pub path: String,
+    pub distance: u32,
+}
+
+/// Copias idénticas de un mismo contenido. `wasted_bytes` es lo que se
+/// liberaría conservando solo una.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateSet {
+    pub hash: String,
+    pub size: u64,
+    pub paths: Vec<String>,
+    pub wasted_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateReport {
+    pub sets: Vec<DuplicateSet>,
+    pub scanned_files: usize,
+    pub wasted_bytes: u64,
+}
+
+/// Elemento de la papelera del sistema que salió de la biblioteca.
+/// `section` es `Biblioteca`, `Recursos`... o `None` si es el cliente entero.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrashedItem {
+    pub id: String,
+    pub name: String,
+    pub original_path: String,
+    pub section: Option<String>,
+    /// Segundos desde UNIX_EPOCH.
+    pub deleted_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrashedClient {
+    pub client: String,
+    pub items: Vec<TrashedItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferStatus {
+    Done,
+    /// Ya estaba en el destino, o se omitió por la política de conflictos.
+    Skipped,
+    /// Existe un elemento con el mismo nombre y la política es `ask`.
+    Conflict,
+    Canceled,
+    Failed,
+}
+
+/// Resultado de un elemento de una transferencia. `destination` es la ruta
+/// final (puede llevar sufijo ` (2)` si se conservaron ambos). `replaced`
+/// indica que lo que había en `destination` se envió a la papelera.
+#[derive(Debug, Serialize)]
+pub struct TransferItem {
+    pub source: String,
+    pub destination: Option<String>,
+    pub status: TransferStatus,
+    pub error: Option<ArchiError>,
+    pub replaced: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferReport {
+    pub job_id: u64,
+    pub items: Vec<TransferItem>,
+    pub bytes_transferred: u64,
+    pub canceled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FsChangeKind {
+    Created,
+    Removed,
+    Renamed,
+    Modified,
+}
+
+/// Cambio en disco dentro del cliente vigilado (evento `fs-changed`).
+/// `folder` es la carpeta afectada en el formato del frontend (`Cliente/Trabajo`)
+/// y `section` indica si está en `Biblioteca` o `Recursos`. En un renombrado
+/// `paths` contiene la ruta anterior y la nueva.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FsChange {
+    pub kind: FsChangeKind,
+    pub section: String,
+    pub folder: String,
+    pub paths: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileList {
     pub files: Vec<FileItem>,
@@ -86,14 +280,20 @@ pub struct FileLayout {
     pub y: f32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Metadata {
+    /// Versión del formato de `.metadatos.json` (ver `migrations.rs`).
+    #[serde(default)]
+    pub schema_version: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fecha: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pin: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub layout: Option<std::collections::HashMap<String, FileLayout>>,
+    /// Campos que esta versión no conoce, conservados al reescribir.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,230 @@
+use std::path::Path;
+
+use crate::cache_index;
+use crate::error::ArchiError;
+use crate::models::{ColorMatch, PaletteColor};
+use crate::thumbnails::{get_candidates, load_thumbnail};
+use crate::validation::library_path;
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+const DEFAULT_COLORS: usize = 6;
+const MAX_COLORS: usize = 16;
+
+/// Píxeles con menos opacidad se ignoran (fondos transparentes de logotipos).
+const MIN_ALPHA: u8 = 128;
+
+/// Máximo de píxeles muestreados por imagen.
+const MAX_SAMPLES: usize = 40_000;
+
+/// Distancia RGB máxima (de 441) para que un color de la paleta cuente como
+/// el buscado.
+const DEFAULT_TOLERANCE: u32 = 40;
+const MAX_TOLERANCE: u32 = 160;
+
+/// Colores que ocupan menos de esta parte de la imagen no cuentan al filtrar.
+const MIN_PROPORTION: f32 = 0.05;
+
+// ==========================================
+// PALETA
+// ==========================================
+
+/// Paleta de `count` colores del archivo, desde el índice o calculada sobre
+/// su miniatura y guardada junto a ella.
+fn file_palette(path: &Path, count: usize) -> Result<Vec<PaletteColor>, String> {
+    if let Some(palette) = cache_index::fingerprint(path)
+        .ok()
+        .and_then(|fingerprint| cache_index::palette(&fingerprint, count))
+    {
+        return Ok(palette);
+    }
+
+    let (fingerprint, img) = load_thumbnail(path)?;
+    let pixels: Vec<[u8; 3]> = {
+        let rgba = img.to_rgba8();
+        let step = (rgba.pixels().len() / MAX_SAMPLES).max(1);
+        rgba.pixels()
+            .step_by(step)
+            .filter(|p| p[3] >= MIN_ALPHA)
+            .map(|p| [p[0], p[1], p[2]])
+            .collect()
+    };
+    if pixels.is_empty() {
+        return Err("La imagen es completamente transparente".to_string());
+    }
+
+    let palette = median_cut(pixels, count);
+    cache_index::record_palette(&fingerprint, count, &palette);
+    Ok(palette)
+}
+
+/// `#rrggbb` (con o sin `#`) a RGB.
+fn parse_hex(color: &str) -> Option<[u8; 3]> {
+    let hex = color.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn color_distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    let sum: u32 = a
+        .iter()
+        .zip(b)
+        .map(|(x, y)| (*x as i32 - y as i32).pow(2) as u32)
+        .sum();
+    (sum as f64).sqrt().round() as u32
+}
+
+/// Color de la paleta más cercano a `target` entre los que ocupan una parte
+/// apreciable de la imagen, si está dentro de `tolerance`.
+fn closest(target: [u8; 3], palette: &[PaletteColor], tolerance: u32) -> Option<(String, u32)> {
+    palette
+        .iter()
+        .filter(|color| color.proportion >= MIN_PROPORTION)
+        .filter_map(|color| {
+            let rgb = parse_hex(&color.hex)?;
+            Some((color.hex.clone(), color_distance(target, rgb)))
+        })
+        .filter(|(_, distance)| *distance <= tolerance)
+        .min_by_key(|(_, distance)| *distance)
+}
+
+// ==========================================
+// COMANDOS
+// ==========================================
+
+/// Paleta dominante de un archivo: colores en hexadecimal ordenados por la
+/// proporción de la imagen que ocupan. Se calcula sobre la miniatura en caché
+/// y se guarda en el índice junto a ella.
+#[tauri::command]
+pub async fn extract_palette(
+    path: String,
+    count: Option<usize>,
+) -> Result<Vec<PaletteColor>, ArchiError> {
+    let file_path = Path::new(&path);
+    if !file_path.is_file() {
+        return Err(ArchiError::NotFound(file_path.to_path_buf()));
+    }
+    // Solo archivos dentro de la carpeta de ARCHI
+    let file_path = library_path(&path)?;
+    let count = count.unwrap_or(DEFAULT_COLORS).clamp(1, MAX_COLORS);
+
+    Ok(file_palette(&file_path, count)?)
+}
+
+/// Archivos de la biblioteca (o solo de `folder`) con un color dominante
+/// parecido a `color` (`#rrggbb`), del más al menos parecido. `tolerance` es
+/// la distancia RGB máxima (por defecto 40). Las paletas que falten se
+/// calculan y quedan en el índice.
+#[tauri::command]
+pub async fn find_by_color(
+    color: String,
+    tolerance: Option<u32>,
+    folder: Option<String>,
+) -> Result<Vec<ColorMatch>, ArchiError> {
+    let target = parse_hex(&color).ok_or_else(|| {
+        ArchiError::Other(format!("\"{}\" no es un color válido (#rrggbb)", color))
+    })?;
+    let tolerance = tolerance.unwrap_or(DEFAULT_TOLERANCE).min(MAX_TOLERANCE);
+    // Solo carpetas dentro de la carpeta de ARCHI
+    let folder = folder.map(|folder| library_path(&folder)).transpose()?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut matches: Vec<ColorMatch> = get_candidates()
+            .into_iter()
+            .filter(|path| {
+                folder
+                    .as_ref()
+                    .is_none_or(|folder| path.starts_with(folder))
+            })
+            .filter_map(|path| {
+                let palette = file_palette(&path, DEFAULT_COLORS).ok()?;
+                let (hex, distance) = closest(target, &palette, tolerance)?;
+                Some(ColorMatch {
+                    path: path.to_string_lossy().to_string(),
+                    hex,
+                    distance,
+                })
+            })
+            .collect();
+        cache_index::flush();
+        matches.sort_by(|a, b| a.distance.cmp(&b.distance).then(a.path.cmp(&b.path)));
+        Ok(matches)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// ==========================================
+// CUANTIZACIÓN (MEDIAN CUT)
+// ==========================================
+
+/// Divide el espacio RGB en `count` cajas partiendo siempre la que tiene más
+/// píxeles por su canal más amplio. Cada caja aporta su color medio.
+fn median_cut(pixels: Vec<[u8; 3]>, count: usize) -> Vec<PaletteColor> {
+    let total = pixels.len();
+    let mut boxes = vec![pixels];
+
+    while boxes.len() < count {
+        // Caja con más peso que todavía se pueda dividir
+        let Some(index) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1 && widest_channel(b).1 > 0)
+            .max_by_key(|(_, b)| b.len() * widest_channel(b).1 as usize)
+            .map(|(i, _)| i)
+        else {
+            break;
+        };
+
+        let mut target = boxes.swap_remove(index);
+        let (channel, _) = widest_channel(&target);
+        target.sort_unstable_by_key(|p| p[channel]);
+        let upper = target.split_off(target.len() / 2);
+        boxes.push(target);
+        boxes.push(upper);
+    }
+
+    let mut palette: Vec<(usize, [u8; 3])> = boxes
+        .iter()
+        .map(|b| {
+            let mut sum = [0u64; 3];
+            for p in b {
+                for (s, v) in sum.iter_mut().zip(p) {
+                    *s += *v as u64;
+                }
+            }
+            let n = b.len() as u64;
+            (b.len(), sum.map(|s| (s / n) as u8))
+        })
+        .collect();
+    palette.sort_by_key(|(n, _)| std::cmp::Reverse(*n));
+
+    palette
+        .into_iter()
+        .map(|(n, [r, g, b])| PaletteColor {
+            hex: format!("#{:02x}{:02x}{:02x}", r, g, b),
+            proportion: n as f32 / total as f32,
+        })
+        .collect()
+}
+
+/// Canal (0 = R, 1 = G, 2 = B) con mayor rango en la caja y ese rango.
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    let mut min = [u8::MAX; 3];
+    let mut max = [0u8; 3];
+    for p in pixels {
+        for ((lo, hi), v) in min.iter_mut().zip(max.iter_mut()).zip(p) {
+            *lo = (*lo).min(*v);
+            *hi = (*hi).max(*v);
+        }
+    }
+    (0..3)
+        .map(|c| (c, max[c] - min[c]))
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or((0, 0))
+}
@@ -1,33 +1,138 @@
-use crate::models::AppConfig;
+use crate::error::ArchiError;
+use crate::metadata_store;
+use crate::migrations::{config_version, parse, Schema};
+use crate::models::{AppConfig, LibraryRoot};
+use sha2::{Digest, Sha256};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 
 // ==========================================
 // CONSTANTES Y RUTAS
 // ==========================================
 
-pub fn get_user_data_dir() -> PathBuf {
+/// Biblioteca abierta. Se resuelve desde la configuración la primera vez y
+/// solo cambia con `set_active_library`.
+static ACTIVE_LIBRARY: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Carpeta de la aplicación (`~/ARCHI`): guarda `config.json` y es la
+/// biblioteca por defecto. Sin directorio de inicio se usa el de datos del
+/// sistema o, en último caso, el temporal.
+pub fn get_default_library_dir() -> PathBuf {
     dirs::home_dir()
-        .expect("No se pudo encontrar el directorio de inicio")
+        .or_else(dirs::data_dir)
+        .unwrap_or_else(std::env::temp_dir)
         .join("ARCHI")
 }
 
-pub fn get_cache_dir() -> PathBuf {
+/// Raíz de la biblioteca activa, donde viven las carpetas de los clientes.
+pub fn get_user_data_dir() -> PathBuf {
+    if let Some(root) = ACTIVE_LIBRARY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+    {
+        return root;
+    }
+
+    let root = active_library_root(&get_config());
+    *ACTIVE_LIBRARY.write().unwrap_or_else(|e| e.into_inner()) = Some(root.clone());
+    root
+}
+
+/// Cambia la biblioteca activa para el resto de la sesión.
+pub fn set_active_library(root: PathBuf) {
+    *ACTIVE_LIBRARY.write().unwrap_or_else(|e| e.into_inner()) = Some(root);
+}
+
+/// Biblioteca activa según `config`. Si no está registrada o no está
+/// accesible se abre la biblioteca por defecto.
+fn active_library_root(config: &AppConfig) -> PathBuf {
+    let active = PathBuf::from(&config.active_library);
+    let registered = config
+        .library_roots
+        .iter()
+        .any(|root| Path::new(&root.path) == active);
+
+    if registered && active.is_dir() {
+        return active;
+    }
+    eprintln!(
+        "Biblioteca {} no disponible, se abre la biblioteca por defecto",
+        active.display()
+    );
+    get_default_library_dir()
+}
+
+/// Biblioteca registrada en `root`, si la hay.
+pub fn find_library(config: &AppConfig, root: &Path) -> Option<LibraryRoot> {
+    config
+        .library_roots
+        .iter()
+        .find(|library| Path::new(&library.path) == root)
+        .cloned()
+}
+
+/// Carpeta común de las cachés de todas las bibliotecas.
+pub(crate) fn get_cache_base_dir() -> PathBuf {
     #[cfg(target_os = "macos")]
     {
         dirs::cache_dir()
-            .expect("No se pudo encontrar el directorio de caché")
-            .join("ARCHI")
+            .map(|dir| dir.join("ARCHI"))
+            .unwrap_or_else(get_default_library_dir)
             .join("cache")
     }
     #[cfg(not(target_os = "macos"))]
     {
-        get_user_data_dir().join("cache")
+        get_default_library_dir().join("cache")
+    }
+}
+
+/// Caché de la biblioteca en `root`. Las cachés quedan siempre en el equipo
+/// local, también para bibliotecas en discos externos o compartidas.
+pub fn get_library_cache_dir(root: &Path) -> PathBuf {
+    let digest = Sha256::digest(root.to_string_lossy().as_bytes());
+    let id: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    get_cache_base_dir().join(id)
+}
+
+pub fn get_cache_dir() -> PathBuf {
+    get_library_cache_dir(&get_user_data_dir())
+}
+
+/// Las versiones anteriores guardaban la caché directamente en la carpeta
+/// común; se mueve a la de la biblioteca por defecto.
+pub fn migrate_legacy_cache() {
+    let base = get_cache_base_dir();
+    if !base.join("index.json").is_file() {
+        return;
+    }
+    let target = get_library_cache_dir(&get_default_library_dir());
+    if fs::create_dir_all(&target).is_err() {
+        return;
+    }
+
+    if let Ok(entries) = fs::read_dir(&base) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() {
+                let _ = fs::rename(&path, target.join(entry.file_name()));
+            }
+        }
     }
 }
 
 pub fn get_config_path() -> PathBuf {
-    get_user_data_dir().join("config.json")
+    get_default_library_dir().join("config.json")
+}
+
+/// `config.json` o una de las copias que deja al guardarse o si está dañado.
+pub(crate) fn is_config_file(path: &Path) -> bool {
+    let config_path = get_config_path();
+    path.parent() == config_path.parent()
+        && path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("config.json"))
 }
 
 // ==========================================
@@ -39,25 +144,43 @@ pub fn get_config() -> AppConfig {
     let config_path = get_config_path();
     let default_config = AppConfig::default();
 
-    if config_path.exists() {
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            if let Ok(user_config) = serde_json::from_str::<AppConfig>(&content) {
+    match fs::read_to_string(&config_path) {
+        Ok(content) => match parse::<AppConfig>(&content, Schema::Config) {
+            Ok((user_config, migrated)) => {
                 // Asegurarse de que no falten claves (serde maneja esto parcialmente con default,
                 // pero si queremos fusionar valores por defecto explícitos para claves faltantes,
                 // podríamos necesitar más lógica. Por ahora, confiamos en los valores por defecto de serde si faltan campos)
+                if migrated {
+                    let _ = save_config(user_config.clone());
+                }
                 return user_config;
             }
+            // Inválido: se conserva una copia (`config.json.corrupto-<segundos>`)
+            // antes de sustituirlo; si no se puede, no se toca
+            Err(_) => {
+                if metadata_store::backup_corrupt(&config_path).is_err() {
+                    return default_config;
+                }
+            }
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        // Existe pero no se pudo leer: no se sobrescribe
+        Err(e) => {
+            eprintln!("No se pudo leer {}: {}", config_path.display(), e);
+            return default_config;
         }
     }
 
-    // Si el archivo no existe o es inválido, guardar por defecto y retornarlo
+    // Si el archivo no existe o era inválido, guardar por defecto y retornarlo
     let _ = save_config(default_config.clone());
     default_config
 }
 
 #[tauri::command]
-pub fn save_config(config: AppConfig) -> Result<(), String> {
+pub fn save_config(mut config: AppConfig) -> Result<(), ArchiError> {
     let config_path = get_config_path();
+    // Si lo escribió una versión más nueva se mantiene su número
+    config.schema_version = config.schema_version.max(config_version());
     let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
 
     // Asegurar que el directorio exista
@@ -65,12 +188,12 @@ pub fn save_config(config: AppConfig) -> Result<(), String> {
         fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
 
-    fs::write(config_path, json).map_err(|e| e.to_string())?;
+    metadata_store::write_atomic(&config_path, json.as_bytes())?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn update_module(module_id: String) -> Result<(), String> {
+pub fn update_module(module_id: String) -> Result<(), ArchiError> {
     if module_id == "M-Settings" {
         return Ok(());
     }
@@ -87,3 +210,7 @@ pub fn get_default_config() -> AppConfig {
 pub fn get_cached_path(hash: &str) -> PathBuf {
     get_cache_dir().join(format!("{}.webp", hash))
 }
+
+pub fn get_cache_index_path() -> PathBuf {
+    get_cache_dir().join("index.json")
+}
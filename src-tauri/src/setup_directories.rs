@@ -1,4 +1,5 @@
-use crate::paths::{get_cache_dir, get_config, get_user_data_dir};
+use crate::error::ArchiError;
+use crate::paths::{get_cache_dir, get_config, get_user_data_dir, migrate_legacy_cache};
 use std::fs;
 
 // ==========================================
@@ -6,7 +7,7 @@ use std::fs;
 // ==========================================
 
 #[tauri::command]
-pub fn ensure_user_setup() -> Result<(), String> {
+pub fn ensure_user_setup() -> Result<(), ArchiError> {
     let user_data_dir = get_user_data_dir();
 
     // 1. Crear directorio principal
@@ -17,22 +18,21 @@ pub fn ensure_user_setup() -> Result<(), String> {
     // 2. Asegurar que existe configuración
     let _ = get_config();
 
-    // 3. Asegurar que existe directorio de caché
+    // 3. Asegurar que existe directorio de caché (uno por biblioteca)
+    migrate_legacy_cache();
     let cache_dir = get_cache_dir();
     if !cache_dir.exists() {
         let _ = fs::create_dir_all(&cache_dir);
     }
 
-    // 4. Iniciar limpieza de caché en segundo plano
-    crate::thumbnails::cache_cleanup();
-
-    // 5. Asegurar subcarpetas para cada cliente
+    // 4. Asegurar subcarpetas para cada cliente
     ensure_client_subfolders(&user_data_dir)?;
 
     Ok(())
 }
 
-fn ensure_client_subfolders(user_data_dir: &std::path::Path) -> Result<(), String> {
+/// Crea las subcarpetas que falten en cada cliente de `user_data_dir`.
+pub(crate) fn ensure_client_subfolders(user_data_dir: &std::path::Path) -> Result<(), String> {
     let required_subfolders = ["Recursos", "Biblioteca", "Contaduria", "Notas"];
 
     let entries = fs::read_dir(user_data_dir).map_err(|e| e.to_string())?;
@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+
+use crate::cache_index;
+use crate::error::ArchiError;
+use crate::models::SimilarFile;
+use crate::thumbnails::{decode_image, get_candidates};
+use crate::validation::library_path;
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+/// Bits distintos (de 64) por debajo de los cuales dos imágenes se consideran
+/// la misma obra: cubre cambios de tamaño, compresión y retoques leves.
+const DEFAULT_THRESHOLD: u32 = 10;
+const MAX_THRESHOLD: u32 = 32;
+
+// ==========================================
+// HASH PERCEPTUAL
+// ==========================================
+
+/// dHash de 64 bits: la imagen en grises reducida a 9×8 y, por fila, si cada
+/// píxel es más claro que su vecino de la derecha.
+pub(crate) fn dhash(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | (left > right) as u64;
+        }
+    }
+    hash
+}
+
+fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Hash del archivo, calculándolo sobre la imagen completa si el índice no lo
+/// tiene (miniaturas generadas antes de guardar hashes). Los fallos también se
+/// guardan para no decodificar el archivo en cada búsqueda.
+fn file_hash(path: &Path) -> Result<u64, String> {
+    let fingerprint = cache_index::fingerprint(path)?;
+    if let Some(hash) = cache_index::phash(&fingerprint) {
+        return Ok(hash);
+    }
+    if cache_index::is_undecodable(&fingerprint) {
+        return Err("No se pudo leer la imagen".to_string());
+    }
+
+    match decode_image(path) {
+        Ok(img) => {
+            let hash = dhash(&img);
+            cache_index::record_phash(&fingerprint, hash);
+            Ok(hash)
+        }
+        Err(e) => {
+            cache_index::record_undecodable(&fingerprint);
+            Err(e)
+        }
+    }
+}
+
+/// Hash de cada archivo de la biblioteca. Los que no se pueden leer se omiten.
+fn library_hashes() -> Vec<(PathBuf, u64)> {
+    let hashes = get_candidates()
+        .into_iter()
+        .filter_map(|path| file_hash(&path).ok().map(|hash| (path, hash)))
+        .collect();
+    cache_index::flush();
+    hashes
+}
+
+// ==========================================
+// COMANDOS
+// ==========================================
+
+/// Archivos de la biblioteca parecidos a `path`, del más al menos parecido.
+/// `threshold` es la distancia máxima en bits (por defecto 10).
+#[tauri::command]
+pub async fn find_similar(
+    path: String,
+    threshold: Option<u32>,
+) -> Result<Vec<SimilarFile>, ArchiError> {
+    let file_path = PathBuf::from(&path);
+    if !file_path.is_file() {
+        return Err(ArchiError::NotFound(file_path));
+    }
+    // Solo archivos dentro de la carpeta de ARCHI
+    let file_path = library_path(&path)?;
+    let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD).min(MAX_THRESHOLD);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let target = file_hash(&file_path)?;
+
+        let mut similar: Vec<SimilarFile> = library_hashes()
+            .into_iter()
+            .filter(|(candidate, _)| *candidate != file_path)
+            .filter_map(|(candidate, hash)| {
+                let distance = distance(target, hash);
+                (distance <= threshold).then(|| SimilarFile {
+                    path: candidate.to_string_lossy().to_string(),
+                    distance,
+                })
+            })
+            .collect();
+        similar.sort_by(|a, b| a.distance.cmp(&b.distance).then(a.path.cmp(&b.path)));
+        Ok(similar)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Informe de casi duplicados: grupos de archivos de la biblioteca conectados
+/// por parecidos dentro de `threshold`, los más numerosos primero.
+#[tauri::command]
+pub async fn find_similar_groups(threshold: Option<u32>) -> Result<Vec<Vec<String>>, ArchiError> {
+    let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD).min(MAX_THRESHOLD);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let hashes = library_hashes();
+
+        // Unión de conjuntos: si A se parece a B y B a C, los tres van juntos
+        let mut parent: Vec<usize> = (0..hashes.len()).collect();
+        for i in 0..hashes.len() {
+            for j in (i + 1)..hashes.len() {
+                if distance(hashes[i].1, hashes[j].1) <= threshold {
+                    let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                    parent[b] = a;
+                }
+            }
+        }
+
+        let mut groups: Vec<Vec<String>> = vec![Vec::new(); hashes.len()];
+        for (i, (path, _)) in hashes.iter().enumerate() {
+            let r = root(&mut parent, i);
+            groups[r].push(path.to_string_lossy().to_string());
+        }
+
+        let mut groups: Vec<Vec<String>> = groups.into_iter().filter(|g| g.len() > 1).collect();
+        for group in &mut groups {
+            group.sort();
+        }
+        groups.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+        Ok(groups)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
@@ -0,0 +1,424 @@
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use base64::{engine::general_purpose, Engine as _};
+use image::{DynamicImage, ImageFormat};
+
+use super::ThumbnailBackend;
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+/// Máximo de bytes que se leen del principio del archivo para buscar
+/// vistas previas. Los paquetes XMP y las primeras páginas están al
+/// principio en la práctica.
+const MAX_SCAN_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Tamaño máximo de la sección TIFF o WMF de un EPS binario. Se leen
+/// directamente desde su posición en la cabecera, estén donde estén.
+const MAX_EPS_PREVIEW_BYTES: u32 = 32 * 1024 * 1024;
+
+/// Firma de la cabecera binaria "DOS EPS".
+const DOS_EPS_MAGIC: &[u8] = &[0xC5, 0xD0, 0xD3, 0xC6];
+
+/// Nombres de la propiedad de miniatura XMP (el prefijo `xap` es de versiones antiguas).
+const XMP_THUMBNAIL_TAGS: &[&[u8]] = &[b"xmpGImg:image", b"xapGImg:image"];
+
+/// Registros WMF que transportan un bitmap DIB y el desplazamiento del DIB dentro del registro.
+const WMF_DIB_RECORDS: &[(u16, usize)] = &[
+    (0x0F43, 28), // META_STRETCHDIB
+    (0x0B41, 26), // META_DIBSTRETCHBLT
+    (0x0940, 22), // META_DIBBITBLT
+];
+
+// ==========================================
+// BACKEND
+// ==========================================
+
+/// Vistas previas embebidas en Illustrator, EPS e InDesign.
+pub struct EmbeddedPreviewBackend;
+
+impl ThumbnailBackend for EmbeddedPreviewBackend {
+    fn name(&self) -> &'static str {
+        "embedded"
+    }
+
+    fn supports(&self, kind: &str) -> bool {
+        matches!(kind, "ai" | "eps" | "indd")
+    }
+
+    fn generate(&self, path: &Path, _size: u32) -> Result<DynamicImage, String> {
+        let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+        let kind = super::file_kind(path);
+
+        if kind == "eps" {
+            if let Some(img) = dos_eps_preview(&mut file) {
+                return Ok(img);
+            }
+            file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+        }
+
+        let mut data = Vec::new();
+        file.take(MAX_SCAN_BYTES)
+            .read_to_end(&mut data)
+            .map_err(|e| e.to_string())?;
+
+        let preview = match kind.as_str() {
+            "ai" => xmp_thumbnail(&data).or_else(|| pdf_first_page_jpeg(&data)),
+            _ => xmp_thumbnail(&data),
+        };
+
+        preview.ok_or_else(|| "El archivo no contiene vista previa embebida".to_string())
+    }
+}
+
+// ==========================================
+// EPS (CABECERA BINARIA DOS)
+// ==========================================
+
+/// Cabecera de 30 bytes: firma, luego pares (offset, longitud) little-endian
+/// para PostScript, WMF y TIFF. Solo se leen la cabecera y las secciones.
+fn dos_eps_preview(file: &mut File) -> Option<DynamicImage> {
+    let mut header = [0u8; 30];
+    file.read_exact(&mut header).ok()?;
+    if !header.starts_with(DOS_EPS_MAGIC) {
+        return None;
+    }
+
+    let tiff = section(file, read_u32_le(&header, 20)?, read_u32_le(&header, 24)?);
+    if let Some(img) =
+        tiff.and_then(|t| image::load_from_memory_with_format(&t, ImageFormat::Tiff).ok())
+    {
+        return Some(img);
+    }
+
+    let wmf = section(file, read_u32_le(&header, 12)?, read_u32_le(&header, 16)?)?;
+    let bmp = wmf_dib_as_bmp(&wmf)?;
+    image::load_from_memory_with_format(&bmp, ImageFormat::Bmp).ok()
+}
+
+fn section(file: &mut File, offset: u32, len: u32) -> Option<Vec<u8>> {
+    if offset == 0 || len == 0 || len > MAX_EPS_PREVIEW_BYTES {
+        return None;
+    }
+    file.seek(SeekFrom::Start(offset as u64)).ok()?;
+    let mut data = vec![0u8; len as usize];
+    file.read_exact(&mut data).ok()?;
+    Some(data)
+}
+
+/// Las vistas previas WMF de los EPS son un único bitmap dentro de un registro
+/// StretchDIB/DIBBitBlt; se extrae el DIB y se le antepone una cabecera BMP.
+fn wmf_dib_as_bmp(wmf: &[u8]) -> Option<Vec<u8>> {
+    // Cabecera "placeable" opcional de 22 bytes
+    let mut pos = if wmf.starts_with(&[0xD7, 0xCD, 0xC6, 0x9A]) {
+        22
+    } else {
+        0
+    };
+
+    // META_HEADER: el tamaño de cabecera viene en palabras de 16 bits
+    let header_words = read_u16_le(wmf, pos + 2)? as usize;
+    pos += header_words * 2;
+
+    while pos + 6 <= wmf.len() {
+        let record_len = read_u32_le(wmf, pos)? as usize * 2;
+        let function = read_u16_le(wmf, pos + 4)?;
+        if record_len < 6 || function == 0 {
+            break;
+        }
+
+        if let Some((_, dib_offset)) = WMF_DIB_RECORDS.iter().find(|(f, _)| *f == function) {
+            let record = wmf.get(pos..pos + record_len)?;
+            if let Some(bmp) = record.get(*dib_offset..).and_then(dib_to_bmp) {
+                return Some(bmp);
+            }
+        }
+
+        pos += record_len;
+    }
+
+    None
+}
+
+fn dib_to_bmp(dib: &[u8]) -> Option<Vec<u8>> {
+    let header_size = read_u32_le(dib, 0)?;
+    if !matches!(header_size, 12 | 40 | 52 | 56 | 108 | 124) {
+        return None;
+    }
+
+    let palette_len = if header_size == 12 {
+        let bit_count = read_u16_le(dib, 10)?;
+        if bit_count <= 8 {
+            3 * (1usize << bit_count)
+        } else {
+            0
+        }
+    } else {
+        let bit_count = read_u16_le(dib, 14)?;
+        let compression = read_u32_le(dib, 16)?;
+        let colors_used = read_u32_le(dib, 32)? as usize;
+        let colors = if colors_used > 0 {
+            colors_used
+        } else if bit_count <= 8 {
+            1usize << bit_count
+        } else {
+            0
+        };
+        // BI_BITFIELDS con cabecera de 40 bytes: las máscaras van después de la cabecera
+        let masks = if compression == 3 && header_size == 40 {
+            12
+        } else {
+            0
+        };
+        colors * 4 + masks
+    };
+
+    let pixel_offset = 14 + header_size as usize + palette_len;
+    let file_size = 14 + dib.len();
+
+    let mut bmp = Vec::with_capacity(file_size);
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&(file_size as u32).to_le_bytes());
+    bmp.extend_from_slice(&[0, 0, 0, 0]);
+    bmp.extend_from_slice(&(pixel_offset as u32).to_le_bytes());
+    bmp.extend_from_slice(dib);
+    Some(bmp)
+}
+
+// ==========================================
+// XMP (AI / INDD / EPS)
+// ==========================================
+
+/// Busca la primera miniatura `xmpGImg:image` (JPEG en base64) del paquete XMP.
+fn xmp_thumbnail(data: &[u8]) -> Option<DynamicImage> {
+    for tag in XMP_THUMBNAIL_TAGS {
+        let mut from = 0;
+        while let Some(at) = find(data, tag, from) {
+            from = at + tag.len();
+            let Some(encoded) = xmp_value(&data[from..]) else {
+                continue;
+            };
+            if let Some(img) = decode_base64_jpeg(encoded) {
+                return Some(img);
+            }
+        }
+    }
+    None
+}
+
+/// Valor de la propiedad, tanto en forma de elemento (`>...<`) como de atributo (`="..."`).
+fn xmp_value(rest: &[u8]) -> Option<&[u8]> {
+    match rest.first()? {
+        b'>' => {
+            let end = rest.iter().position(|&b| b == b'<')?;
+            Some(&rest[1..end])
+        }
+        b'=' => {
+            let quote = *rest.get(1)?;
+            if quote != b'"' && quote != b'\'' {
+                return None;
+            }
+            let end = rest[2..].iter().position(|&b| b == quote)?;
+            Some(&rest[2..2 + end])
+        }
+        _ => None,
+    }
+}
+
+fn decode_base64_jpeg(encoded: &[u8]) -> Option<DynamicImage> {
+    // Los saltos de línea vienen escapados como entidades XML (&#xA;)
+    let text = String::from_utf8_lossy(encoded)
+        .replace("&#xA;", "")
+        .replace("&#xa;", "")
+        .replace("&#xD;", "")
+        .replace("&#xd;", "")
+        .replace("&#10;", "")
+        .replace("&#13;", "");
+    let clean: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if clean.is_empty() {
+        return None;
+    }
+
+    let jpeg = general_purpose::STANDARD.decode(clean).ok()?;
+    image::load_from_memory_with_format(&jpeg, ImageFormat::Jpeg).ok()
+}
+
+// ==========================================
+// PDF (AI CON COMPATIBILIDAD PDF)
+// ==========================================
+
+/// JPEG más grande referenciado por la primera página, o el primer flujo
+/// DCTDecode del archivo si el árbol de páginas está en flujos comprimidos.
+fn pdf_first_page_jpeg(data: &[u8]) -> Option<DynamicImage> {
+    // Solo los AI guardados con compatibilidad PDF tienen estructura PDF
+    find(&data[..data.len().min(1024)], b"%PDF", 0)?;
+
+    let from_page = first_page_image_objects(data).and_then(|objects| {
+        objects
+            .into_iter()
+            .filter_map(|num| find_object(data, num))
+            .filter(|obj| find(obj, b"/DCTDecode", 0).is_some())
+            .filter_map(stream_data)
+            .max_by_key(|s| s.len())
+            .and_then(|jpeg| image::load_from_memory_with_format(jpeg, ImageFormat::Jpeg).ok())
+    });
+    if from_page.is_some() {
+        return from_page;
+    }
+
+    let mut from = 0;
+    while let Some(at) = find(data, b"/DCTDecode", from) {
+        from = at + 1;
+        let obj_end = find(data, b"endobj", at).unwrap_or(data.len());
+        if let Some(img) = stream_data(&data[at..obj_end])
+            .and_then(|jpeg| image::load_from_memory_with_format(jpeg, ImageFormat::Jpeg).ok())
+        {
+            return Some(img);
+        }
+    }
+    None
+}
+
+/// Números de objeto de los XObjects de la primera página encontrada.
+fn first_page_image_objects(data: &[u8]) -> Option<Vec<u32>> {
+    let page = first_page_object(data)?;
+    let resources = dict_value(data, page, b"/Resources")?;
+    let xobjects = dict_value(data, resources, b"/XObject")?;
+    Some(references(xobjects))
+}
+
+fn first_page_object(data: &[u8]) -> Option<&[u8]> {
+    for pattern in [&b"/Type/Page"[..], &b"/Type /Page"[..]] {
+        let mut from = 0;
+        while let Some(at) = find(data, pattern, from) {
+            from = at + pattern.len();
+            // Descartar "/Pages"
+            if data.get(from) == Some(&b's') {
+                continue;
+            }
+            let start = rfind(&data[..at], b"obj").map(|p| p + 3).unwrap_or(0);
+            let end = find(data, b"endobj", at).unwrap_or(data.len());
+            return Some(&data[start..end]);
+        }
+    }
+    None
+}
+
+/// Valor de `key` dentro de `obj`: un diccionario en línea o una referencia resuelta.
+fn dict_value<'a>(data: &'a [u8], obj: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    let at = find(obj, key, 0)? + key.len();
+    let rest = trim_start(&obj[at..]);
+
+    if rest.starts_with(b"<<") {
+        return balanced_dict(rest);
+    }
+
+    let num = references(&rest[..rest.len().min(32)]).into_iter().next()?;
+    let target = find_object(data, num)?;
+    let start = find(target, b"<<", 0)?;
+    balanced_dict(&target[start..])
+}
+
+fn balanced_dict(text: &[u8]) -> Option<&[u8]> {
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i + 1 < text.len() {
+        match &text[i..i + 2] {
+            b"<<" => {
+                depth += 1;
+                i += 2;
+            }
+            b">>" => {
+                depth = depth.checked_sub(1)?;
+                i += 2;
+                if depth == 0 {
+                    return Some(&text[..i]);
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Extrae los números de objeto de las referencias indirectas (`12 0 R`).
+fn references(text: &[u8]) -> Vec<u32> {
+    let tokens: Vec<&[u8]> = text
+        .split(|b| b.is_ascii_whitespace() || matches!(b, b'/' | b'<' | b'>' | b'[' | b']'))
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    tokens
+        .windows(3)
+        .filter(|w| w[2] == b"R" && w[1].iter().all(u8::is_ascii_digit))
+        .filter_map(|w| std::str::from_utf8(w[0]).ok()?.parse().ok())
+        .collect()
+}
+
+fn find_object(data: &[u8], num: u32) -> Option<&[u8]> {
+    let header = format!("{} 0 obj", num);
+    let mut from = 0;
+    while let Some(at) = find(data, header.as_bytes(), from) {
+        from = at + 1;
+        // Evitar coincidencias parciales ("112 0 obj" al buscar "12 0 obj")
+        if at > 0 && data[at - 1].is_ascii_digit() {
+            continue;
+        }
+        let end = find(data, b"endobj", at).unwrap_or(data.len());
+        return Some(&data[at..end]);
+    }
+    None
+}
+
+fn stream_data(obj: &[u8]) -> Option<&[u8]> {
+    let mut start = find(obj, b"stream", 0)? + b"stream".len();
+    if obj.get(start) == Some(&b'\r') {
+        start += 1;
+    }
+    if obj.get(start) == Some(&b'\n') {
+        start += 1;
+    }
+    let end = find(obj, b"endstream", start)?;
+    let data = &obj[start..end];
+    let trimmed = data.len()
+        - data
+            .iter()
+            .rev()
+            .take_while(|b| **b == b'\r' || **b == b'\n')
+            .count();
+    Some(&data[..trimmed])
+}
+
+// ==========================================
+// FUNCIONES AUXILIARES
+// ==========================================
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if needle.is_empty() || from >= haystack.len() {
+        return None;
+    }
+    haystack[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|p| p + from)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+fn trim_start(text: &[u8]) -> &[u8] {
+    let skip = text.iter().take_while(|b| b.is_ascii_whitespace()).count();
+    &text[skip..]
+}
+
+fn read_u16_le(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32_le(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
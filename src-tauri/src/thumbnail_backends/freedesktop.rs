@@ -0,0 +1,340 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use image::DynamicImage;
+
+use super::ThumbnailBackend;
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+/// Tiempo máximo que se espera a un thumbnailer externo antes de matarlo.
+const THUMBNAILER_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Tipos MIME por extensión, solo para los formatos que ARCHI indexa.
+const MIME_TYPES: &[(&str, &[&str])] = &[
+    (
+        "psd",
+        &[
+            "image/vnd.adobe.photoshop",
+            "image/x-psd",
+            "application/x-photoshop",
+        ],
+    ),
+    ("psb", &["image/vnd.adobe.photoshop", "image/x-psd"]),
+    (
+        "ai",
+        &[
+            "application/illustrator",
+            "application/pdf",
+            "application/postscript",
+        ],
+    ),
+    ("pdf", &["application/pdf", "application/x-pdf"]),
+    ("eps", &["image/x-eps", "application/postscript"]),
+    ("indd", &["application/x-adobe-indesign"]),
+    ("tiff", &["image/tiff"]),
+    ("tif", &["image/tiff"]),
+    ("raw", &["image/x-panasonic-raw", "image/x-dcraw"]),
+    ("dng", &["image/x-adobe-dng", "image/x-dcraw"]),
+    (
+        "cdr",
+        &[
+            "application/vnd.corel-draw",
+            "image/x-coreldraw",
+            "application/coreldraw",
+        ],
+    ),
+    ("svg", &["image/svg+xml"]),
+    ("doc", &["application/msword"]),
+    (
+        "docx",
+        &["application/vnd.openxmlformats-officedocument.wordprocessingml.document"],
+    ),
+    ("xls", &["application/vnd.ms-excel"]),
+    (
+        "xlsx",
+        &["application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"],
+    ),
+    ("ppt", &["application/vnd.ms-powerpoint"]),
+    (
+        "pptx",
+        &["application/vnd.openxmlformats-officedocument.presentationml.presentation"],
+    ),
+];
+
+// ==========================================
+// MODELO
+// ==========================================
+
+/// Entrada `[Thumbnailer Entry]` de un archivo `.thumbnailer`.
+struct Thumbnailer {
+    exec: String,
+    mime_types: Vec<String>,
+}
+
+/// Thumbnailers instalados según la especificación de freedesktop.org
+/// (`<XDG_DATA_DIRS>/thumbnailers/*.thumbnailer`).
+pub struct FreedesktopBackend {
+    thumbnailers: Vec<Thumbnailer>,
+}
+
+impl FreedesktopBackend {
+    /// Escanea los directorios de datos XDG una sola vez al registrar el backend.
+    pub fn load() -> Self {
+        let mut thumbnailers = Vec::new();
+
+        for dir in thumbnailer_dirs() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("thumbnailer") {
+                    continue;
+                }
+                if let Some(t) = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|c| parse_thumbnailer(&c))
+                {
+                    thumbnailers.push(t);
+                }
+            }
+        }
+
+        Self { thumbnailers }
+    }
+
+    fn find_for(&self, kind: &str) -> Option<&Thumbnailer> {
+        let mimes = mime_types_for(kind);
+        self.thumbnailers
+            .iter()
+            .find(|t| t.mime_types.iter().any(|m| mimes.contains(&m.as_str())))
+    }
+}
+
+impl ThumbnailBackend for FreedesktopBackend {
+    fn name(&self) -> &'static str {
+        "freedesktop"
+    }
+
+    fn supports(&self, kind: &str) -> bool {
+        self.find_for(kind).is_some()
+    }
+
+    fn generate(&self, path: &Path, size: u32) -> Result<DynamicImage, String> {
+        let kind = super::file_kind(path);
+        let thumbnailer = self
+            .find_for(&kind)
+            .ok_or("No hay thumbnailer instalado para este tipo de archivo")?;
+
+        let thumbs_dir = std::env::temp_dir().join("archi_thumbs");
+        fs::create_dir_all(&thumbs_dir).map_err(|e| e.to_string())?;
+        let output_path = thumbs_dir.join(unique_output_name());
+
+        let result = run_thumbnailer(thumbnailer, path, &output_path, size).and_then(|_| {
+            image::open(&output_path).map_err(|e| format!("Error decodificando imagen: {}", e))
+        });
+        let _ = fs::remove_file(&output_path);
+        result
+    }
+}
+
+// ==========================================
+// FUNCIONES AUXILIARES
+// ==========================================
+
+fn thumbnailer_dirs() -> Vec<PathBuf> {
+    let mut search_dirs = Vec::new();
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".local").join("share")));
+    if let Some(home) = data_home {
+        search_dirs.push(home.join("thumbnailers"));
+    }
+
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    for dir in data_dirs.split(':').filter(|d| !d.is_empty()) {
+        search_dirs.push(Path::new(dir).join("thumbnailers"));
+    }
+
+    search_dirs
+}
+
+fn parse_thumbnailer(content: &str) -> Option<Thumbnailer> {
+    let mut in_entry = false;
+    let mut try_exec = None;
+    let mut exec = None;
+    let mut mime_types = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_entry = line == "[Thumbnailer Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            match key.trim() {
+                "TryExec" => try_exec = Some(value.trim().to_string()),
+                "Exec" => exec = Some(value.trim().to_string()),
+                "MimeType" => {
+                    mime_types = value
+                        .split(';')
+                        .map(|m| m.trim().to_string())
+                        .filter(|m| !m.is_empty())
+                        .collect()
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let exec = exec?;
+    // Si el ejecutable no está instalado, la entrada no sirve
+    let program = try_exec.or_else(|| split_exec(&exec).into_iter().next())?;
+    if !program_exists(&program) {
+        return None;
+    }
+
+    Some(Thumbnailer { exec, mime_types })
+}
+
+fn program_exists(program: &str) -> bool {
+    let path = Path::new(program);
+    if path.is_absolute() {
+        return path.is_file();
+    }
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+fn mime_types_for(kind: &str) -> &'static [&'static str] {
+    MIME_TYPES
+        .iter()
+        .find(|(ext, _)| *ext == kind)
+        .map(|(_, mimes)| *mimes)
+        .unwrap_or(&[])
+}
+
+fn unique_output_name() -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    format!(
+        "fd-{}-{}.png",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Divide la línea `Exec` en argumentos respetando comillas dobles.
+fn split_exec(exec: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+/// Sustituye los códigos de campo de la especificación (%s, %u, %i, %o, %%).
+fn expand_field_codes(arg: &str, input: &Path, output: &Path, size: u32) -> String {
+    let mut result = String::new();
+    let mut chars = arg.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => result.push_str(&size.to_string()),
+            Some('u') => result.push_str(&file_uri(input)),
+            Some('i') => result.push_str(&input.to_string_lossy()),
+            Some('o') => result.push_str(&output.to_string_lossy()),
+            Some('%') => result.push('%'),
+            _ => {}
+        }
+    }
+    result
+}
+
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn run_thumbnailer(
+    thumbnailer: &Thumbnailer,
+    input: &Path,
+    output: &Path,
+    size: u32,
+) -> Result<(), String> {
+    let args: Vec<String> = split_exec(&thumbnailer.exec)
+        .iter()
+        .map(|a| expand_field_codes(a, input, output, size))
+        .collect();
+    let (program, rest) = args.split_first().ok_or("Línea Exec vacía")?;
+
+    let mut child = Command::new(program)
+        .args(rest)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Error ejecutando {}: {}", program, e))?;
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) if status.success() && output.exists() => return Ok(()),
+            Some(_) => return Err(format!("{} no pudo generar la miniatura", program)),
+            None if started.elapsed() > THUMBNAILER_TIMEOUT => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{} tardó demasiado en responder", program));
+            }
+            None => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
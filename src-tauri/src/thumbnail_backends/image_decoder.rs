@@ -0,0 +1,29 @@
+use std::fs;
+use std::path::Path;
+
+use image::DynamicImage;
+
+use super::ThumbnailBackend;
+
+/// Formatos que el crate `image` decodifica sin ayuda externa.
+const SUPPORTED_KINDS: &[&str] = &[
+    "jpg", "jpeg", "png", "webp", "bmp", "gif", "tiff", "tif", "ico",
+];
+
+/// Decodificación en proceso con el crate `image`.
+pub struct ImageDecoderBackend;
+
+impl ThumbnailBackend for ImageDecoderBackend {
+    fn name(&self) -> &'static str {
+        "image"
+    }
+
+    fn supports(&self, kind: &str) -> bool {
+        SUPPORTED_KINDS.contains(&kind)
+    }
+
+    fn generate(&self, path: &Path, _size: u32) -> Result<DynamicImage, String> {
+        let data = fs::read(path).map_err(|e| e.to_string())?;
+        image::load_from_memory(&data).map_err(|e| format!("Error decodificando imagen: {}", e))
+    }
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use image::DynamicImage;
+
+mod embedded_preview;
+mod image_decoder;
+mod psd;
+mod raw_preview;
+mod svg;
+mod zip_preview;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod freedesktop;
+
+#[cfg(target_os = "macos")]
+mod quicklook;
+
+// ==========================================
+// TRAIT
+// ==========================================
+
+/// Estrategia capaz de producir una miniatura a partir de un archivo.
+///
+/// `thumbnails::generate_thumbnail` recorre los backends registrados en orden
+/// y se queda con el primero que devuelva una imagen.
+pub trait ThumbnailBackend: Send + Sync {
+    /// Nombre corto usado en los mensajes de error.
+    fn name(&self) -> &'static str;
+
+    /// Indica si vale la pena intentar este backend para el tipo de archivo
+    /// (extensión en minúsculas o tipo detectado por magic bytes).
+    fn supports(&self, kind: &str) -> bool;
+
+    /// Genera la miniatura. `size` es el lado mayor deseado en píxeles; los
+    /// backends que decodifican la imagen completa pueden ignorarlo.
+    fn generate(&self, path: &Path, size: u32) -> Result<DynamicImage, String>;
+}
+
+// ==========================================
+// REGISTRO
+// ==========================================
+
+/// Backends disponibles en esta plataforma, en orden de preferencia.
+pub fn registered() -> &'static [Box<dyn ThumbnailBackend>] {
+    static BACKENDS: OnceLock<Vec<Box<dyn ThumbnailBackend>>> = OnceLock::new();
+
+    BACKENDS.get_or_init(|| {
+        let mut backends: Vec<Box<dyn ThumbnailBackend>> = vec![
+            Box::new(zip_preview::ZipPreviewBackend),
+            Box::new(psd::PsdBackend),
+            Box::new(embedded_preview::EmbeddedPreviewBackend),
+            Box::new(raw_preview::RawPreviewBackend),
+            Box::new(svg::SvgBackend),
+            Box::new(image_decoder::ImageDecoderBackend),
+        ];
+
+        #[cfg(target_os = "macos")]
+        backends.push(Box::new(quicklook::QuickLookBackend));
+
+        #[cfg(all(unix, not(target_os = "macos")))]
+        backends.push(Box::new(freedesktop::FreedesktopBackend::load()));
+
+        backends
+    })
+}
+
+/// Tipo de archivo usado para elegir backend: la extensión si existe,
+/// o el tipo detectado por magic bytes en archivos sin extensión.
+pub fn file_kind(path: &Path) -> String {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if !ext.is_empty() => ext.to_lowercase(),
+        _ => crate::filesystem::detect_file_type_by_magic(path).unwrap_or_default(),
+    }
+}
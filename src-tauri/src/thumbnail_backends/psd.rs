@@ -0,0 +1,415 @@
+use std::fs;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use image::{DynamicImage, GrayAlphaImage, GrayImage, ImageFormat, RgbImage, RgbaImage};
+
+use super::ThumbnailBackend;
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+/// Recurso "Thumbnail" de Photoshop 5.0+ (JPEG en RGB).
+const RESOURCE_THUMBNAIL: u16 = 1036;
+/// Recurso "Thumbnail" de Photoshop 4.0 (JPEG con canales en orden BGR).
+const RESOURCE_THUMBNAIL_PS4: u16 = 1033;
+/// Cabecera del recurso de miniatura que precede a los datos JFIF.
+const THUMBNAIL_HEADER_LEN: usize = 28;
+
+const COLOR_MODE_GRAYSCALE: u16 = 1;
+const COLOR_MODE_RGB: u16 = 3;
+const COLOR_MODE_CMYK: u16 = 4;
+const COLOR_MODE_DUOTONE: u16 = 8;
+
+const COMPRESSION_RAW: u16 = 0;
+const COMPRESSION_RLE: u16 = 1;
+
+// ==========================================
+// BACKEND
+// ==========================================
+
+/// Miniaturas de Photoshop (PSD/PSB) sin depender de QuickLook.
+pub struct PsdBackend;
+
+impl ThumbnailBackend for PsdBackend {
+    fn name(&self) -> &'static str {
+        "psd"
+    }
+
+    fn supports(&self, kind: &str) -> bool {
+        matches!(kind, "psd" | "psb")
+    }
+
+    fn generate(&self, path: &Path, size: u32) -> Result<DynamicImage, String> {
+        let file = fs::File::open(path).map_err(|e| e.to_string())?;
+        let mut reader = BufReader::new(file);
+        decode_preview(&mut reader, size.max(1))
+    }
+}
+
+// ==========================================
+// MODELO
+// ==========================================
+
+struct Header {
+    is_psb: bool,
+    channels: u16,
+    height: u32,
+    width: u32,
+    depth: u16,
+    color_mode: u16,
+}
+
+// ==========================================
+// DECODIFICACIÓN
+// ==========================================
+
+fn decode_preview<R: Read + Seek>(r: &mut R, size: u32) -> Result<DynamicImage, String> {
+    let header = read_header(r)?;
+
+    // 1. Color mode data (paleta de indexados / duotono): no se usa
+    let color_data_len = read_u32(r)?;
+    r.seek(SeekFrom::Current(color_data_len as i64))
+        .map_err(|e| e.to_string())?;
+
+    // 2. Image resources: miniatura JPEG embebida, solo si llega al tamaño
+    //    pedido (suele tener unos 160 px)
+    let resources_len = read_u32(r)? as u64;
+    let resources_start = r.stream_position().map_err(|e| e.to_string())?;
+    if let Some(thumb) = read_thumbnail_resource(r, resources_len) {
+        if thumb.width().max(thumb.height()) >= size {
+            return Ok(thumb);
+        }
+    }
+    r.seek(SeekFrom::Start(resources_start + resources_len))
+        .map_err(|e| e.to_string())?;
+
+    // 3. Layer and mask info: solo interesa saber si la imagen fusionada tiene transparencia
+    let layers_len = read_length(r, header.is_psb)?;
+    let layers_start = r.stream_position().map_err(|e| e.to_string())?;
+    let merged_alpha = layers_len > 0 && has_merged_alpha(r, header.is_psb)?;
+    r.seek(SeekFrom::Start(layers_start + layers_len))
+        .map_err(|e| e.to_string())?;
+
+    // 4. Image data: composición fusionada
+    decode_composite(r, &header, size, merged_alpha)
+}
+
+fn read_header<R: Read>(r: &mut R) -> Result<Header, String> {
+    let mut signature = [0u8; 4];
+    r.read_exact(&mut signature).map_err(|e| e.to_string())?;
+    if &signature != b"8BPS" {
+        return Err("Firma PSD inválida".to_string());
+    }
+
+    let version = read_u16(r)?;
+    if version != 1 && version != 2 {
+        return Err(format!("Versión PSD desconocida: {}", version));
+    }
+
+    let mut reserved = [0u8; 6];
+    r.read_exact(&mut reserved).map_err(|e| e.to_string())?;
+
+    Ok(Header {
+        is_psb: version == 2,
+        channels: read_u16(r)?,
+        height: read_u32(r)?,
+        width: read_u32(r)?,
+        depth: read_u16(r)?,
+        color_mode: read_u16(r)?,
+    })
+}
+
+/// Recorre los bloques de recursos y decodifica la miniatura si existe.
+/// Cualquier error de lectura se trata como "sin miniatura".
+fn read_thumbnail_resource<R: Read + Seek>(r: &mut R, len: u64) -> Option<DynamicImage> {
+    let mut consumed = 0u64;
+
+    while consumed + 12 <= len {
+        let mut signature = [0u8; 4];
+        r.read_exact(&mut signature).ok()?;
+        let id = read_u16(r).ok()?;
+
+        // Nombre Pascal rellenado a longitud par (incluyendo el byte de longitud)
+        let name_len = read_u8(r).ok()? as u64;
+        let name_total = (name_len + 1 + 1) & !1;
+        r.seek(SeekFrom::Current((name_total - 1) as i64)).ok()?;
+
+        let data_len = read_u32(r).ok()? as u64;
+        let data_total = (data_len + 1) & !1;
+        consumed += 4 + 2 + name_total + 4 + data_total;
+
+        if (id == RESOURCE_THUMBNAIL || id == RESOURCE_THUMBNAIL_PS4)
+            && data_len as usize > THUMBNAIL_HEADER_LEN
+        {
+            let mut data = vec![0u8; data_len as usize];
+            r.read_exact(&mut data).ok()?;
+            let img = image::load_from_memory_with_format(
+                &data[THUMBNAIL_HEADER_LEN..],
+                ImageFormat::Jpeg,
+            )
+            .ok()?;
+
+            return Some(if id == RESOURCE_THUMBNAIL_PS4 {
+                swap_red_blue(img)
+            } else {
+                img
+            });
+        }
+
+        r.seek(SeekFrom::Current(data_total as i64)).ok()?;
+    }
+
+    None
+}
+
+fn swap_red_blue(img: DynamicImage) -> DynamicImage {
+    let mut rgb = img.to_rgb8();
+    for pixel in rgb.pixels_mut() {
+        pixel.0.swap(0, 2);
+    }
+    DynamicImage::ImageRgb8(rgb)
+}
+
+/// Un número de capas negativo indica que el primer canal alfa de la
+/// imagen fusionada contiene la transparencia del documento.
+fn has_merged_alpha<R: Read>(r: &mut R, is_psb: bool) -> Result<bool, String> {
+    let layer_info_len = read_length(r, is_psb)?;
+    if layer_info_len < 2 {
+        return Ok(false);
+    }
+    let layer_count = read_u16(r)? as i16;
+    Ok(layer_count < 0)
+}
+
+fn decode_composite<R: Read + Seek>(
+    r: &mut R,
+    header: &Header,
+    size: u32,
+    merged_alpha: bool,
+) -> Result<DynamicImage, String> {
+    let compression = read_u16(r)?;
+
+    let bytes_per_sample = match header.depth {
+        8 => 1,
+        16 => 2,
+        d => return Err(format!("Profundidad de bits no soportada: {}", d)),
+    };
+
+    let color_channels = match header.color_mode {
+        COLOR_MODE_GRAYSCALE | COLOR_MODE_DUOTONE => 1,
+        COLOR_MODE_RGB => 3,
+        COLOR_MODE_CMYK => 4,
+        m => return Err(format!("Modo de color no soportado: {}", m)),
+    };
+
+    let total_channels = header.channels as usize;
+    if total_channels < color_channels {
+        return Err("Faltan canales en la imagen fusionada".to_string());
+    }
+    let has_alpha = merged_alpha && total_channels > color_channels;
+    let wanted = color_channels + usize::from(has_alpha);
+
+    let width = header.width as usize;
+    let height = header.height as usize;
+    if width == 0 || height == 0 {
+        return Err("Dimensiones inválidas".to_string());
+    }
+
+    // Submuestreo al vecino más cercano hasta ~2x el tamaño pedido: evita
+    // mantener en memoria la imagen completa de documentos enormes
+    let target = (size as usize * 2).max(1);
+    let step = width.max(height).div_ceil(target).max(1);
+    let out_w = width.div_ceil(step);
+    let out_h = height.div_ceil(step);
+
+    let row_len = width * bytes_per_sample;
+    let mut row = vec![0u8; row_len];
+    let mut planes = vec![vec![0u8; out_w * out_h]; wanted];
+
+    let sample_row = |plane: &mut [u8], y: usize, row: &[u8]| {
+        let offset = (y / step) * out_w;
+        for ox in 0..out_w {
+            // En 16 bits (big-endian) el byte alto es el primero
+            plane[offset + ox] = row[ox * step * bytes_per_sample];
+        }
+    };
+
+    match compression {
+        COMPRESSION_RAW => {
+            for plane in planes.iter_mut() {
+                for y in 0..height {
+                    if y % step == 0 {
+                        r.read_exact(&mut row).map_err(|e| e.to_string())?;
+                        sample_row(plane, y, &row);
+                    } else {
+                        r.seek(SeekFrom::Current(row_len as i64))
+                            .map_err(|e| e.to_string())?;
+                    }
+                }
+            }
+        }
+        COMPRESSION_RLE => {
+            // Tabla de bytes por fila de todos los canales (u16 en PSD, u32 en PSB)
+            let mut counts = Vec::with_capacity(total_channels * height);
+            for _ in 0..total_channels * height {
+                counts.push(if header.is_psb {
+                    read_u32(r)? as usize
+                } else {
+                    read_u16(r)? as usize
+                });
+            }
+
+            let mut packed = Vec::new();
+            for (c, plane) in planes.iter_mut().enumerate() {
+                for y in 0..height {
+                    let len = counts[c * height + y];
+                    if y % step == 0 {
+                        packed.resize(len, 0);
+                        r.read_exact(&mut packed).map_err(|e| e.to_string())?;
+                        unpack_bits(&packed, &mut row);
+                        sample_row(plane, y, &row);
+                    } else {
+                        r.seek(SeekFrom::Current(len as i64))
+                            .map_err(|e| e.to_string())?;
+                    }
+                }
+            }
+        }
+        c => return Err(format!("Compresión de imagen no soportada: {}", c)),
+    }
+
+    build_image(
+        header.color_mode,
+        planes,
+        out_w as u32,
+        out_h as u32,
+        has_alpha,
+    )
+}
+
+/// Descompresión PackBits de una fila. Las filas truncadas quedan rellenas con ceros.
+fn unpack_bits(src: &[u8], dst: &mut [u8]) {
+    dst.fill(0);
+    let mut i = 0;
+    let mut o = 0;
+
+    while i < src.len() && o < dst.len() {
+        let n = src[i] as i8;
+        i += 1;
+
+        if n >= 0 {
+            let count = (n as usize + 1)
+                .min(dst.len() - o)
+                .min(src.len().saturating_sub(i));
+            dst[o..o + count].copy_from_slice(&src[i..i + count]);
+            i += n as usize + 1;
+            o += count;
+        } else if n != -128 {
+            let Some(&value) = src.get(i) else {
+                break;
+            };
+            i += 1;
+            let count = ((1 - n as isize) as usize).min(dst.len() - o);
+            dst[o..o + count].fill(value);
+            o += count;
+        }
+    }
+}
+
+fn build_image(
+    color_mode: u16,
+    planes: Vec<Vec<u8>>,
+    width: u32,
+    height: u32,
+    has_alpha: bool,
+) -> Result<DynamicImage, String> {
+    let pixels = (width * height) as usize;
+    let samples = interleave(&planes, pixels);
+    let invalid = || "Error construyendo la imagen".to_string();
+
+    let img = match (color_mode, has_alpha) {
+        (COLOR_MODE_RGB, true) => DynamicImage::ImageRgba8(
+            RgbaImage::from_raw(width, height, samples).ok_or_else(invalid)?,
+        ),
+        (COLOR_MODE_RGB, false) => {
+            DynamicImage::ImageRgb8(RgbImage::from_raw(width, height, samples).ok_or_else(invalid)?)
+        }
+        (COLOR_MODE_CMYK, _) => {
+            // PSD guarda CMYK invertido (255 = sin tinta), así que basta con multiplicar por K
+            let rgb: Vec<u8> = samples
+                .chunks_exact(planes.len())
+                .flat_map(|px| {
+                    let k = px[3] as u32;
+                    let mut out = vec![
+                        (px[0] as u32 * k / 255) as u8,
+                        (px[1] as u32 * k / 255) as u8,
+                        (px[2] as u32 * k / 255) as u8,
+                    ];
+                    if has_alpha {
+                        out.push(px[4]);
+                    }
+                    out
+                })
+                .collect();
+            if has_alpha {
+                DynamicImage::ImageRgba8(
+                    RgbaImage::from_raw(width, height, rgb).ok_or_else(invalid)?,
+                )
+            } else {
+                DynamicImage::ImageRgb8(RgbImage::from_raw(width, height, rgb).ok_or_else(invalid)?)
+            }
+        }
+        (_, true) => DynamicImage::ImageLumaA8(
+            GrayAlphaImage::from_raw(width, height, samples).ok_or_else(invalid)?,
+        ),
+        (_, false) => DynamicImage::ImageLuma8(
+            GrayImage::from_raw(width, height, samples).ok_or_else(invalid)?,
+        ),
+    };
+
+    Ok(img)
+}
+
+/// Convierte los planos (un canal por plano) en muestras intercaladas por píxel.
+fn interleave(planes: &[Vec<u8>], pixels: usize) -> Vec<u8> {
+    let mut samples = Vec::with_capacity(pixels * planes.len());
+    for i in 0..pixels {
+        samples.extend(planes.iter().map(|plane| plane[i]));
+    }
+    samples
+}
+
+// ==========================================
+// LECTURA BIG-ENDIAN
+// ==========================================
+
+fn read_u8<R: Read>(r: &mut R) -> Result<u8, String> {
+    let mut buf = [0u8; 1];
+    r.read_exact(&mut buf).map_err(|e| e.to_string())?;
+    Ok(buf[0])
+}
+
+fn read_u16<R: Read>(r: &mut R) -> Result<u16, String> {
+    let mut buf = [0u8; 2];
+    r.read_exact(&mut buf).map_err(|e| e.to_string())?;
+    Ok(u16::from_be_bytes(buf))
+}
+
+fn read_u32<R: Read>(r: &mut R) -> Result<u32, String> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf).map_err(|e| e.to_string())?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+/// Longitudes de sección: 4 bytes en PSD, 8 bytes en PSB.
+fn read_length<R: Read>(r: &mut R, is_psb: bool) -> Result<u64, String> {
+    if is_psb {
+        let mut buf = [0u8; 8];
+        r.read_exact(&mut buf).map_err(|e| e.to_string())?;
+        Ok(u64::from_be_bytes(buf))
+    } else {
+        Ok(read_u32(r)? as u64)
+    }
+}
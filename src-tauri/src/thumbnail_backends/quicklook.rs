@@ -0,0 +1,86 @@
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use image::DynamicImage;
+
+use super::ThumbnailBackend;
+
+/// Miniaturas del sistema mediante QuickLook (`qlmanage`), solo macOS.
+pub struct QuickLookBackend;
+
+impl ThumbnailBackend for QuickLookBackend {
+    fn name(&self) -> &'static str {
+        "qlmanage"
+    }
+
+    fn supports(&self, _kind: &str) -> bool {
+        // QuickLook delega en los plugins del sistema, así que se intenta con todo
+        true
+    }
+
+    fn generate(&self, file_path: &Path, size: u32) -> Result<DynamicImage, String> {
+        // Carpeta propia por llamada: qlmanage nombra la salida según el archivo de
+        // entrada y dos archivos homónimos pueden generarse a la vez
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let thumbs_dir = std::env::temp_dir().join("archi_thumbs").join(format!(
+            "ql-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&thumbs_dir).map_err(|e| e.to_string())?;
+
+        let result = run_qlmanage(file_path, &thumbs_dir, size);
+        let _ = fs::remove_dir_all(&thumbs_dir);
+        result
+    }
+}
+
+fn run_qlmanage(file_path: &Path, thumbs_dir: &Path, size: u32) -> Result<DynamicImage, String> {
+    let mut target_path_buf = file_path.to_path_buf();
+
+    // Manejo de archivos sin extensión: qlmanage necesita la extensión para elegir plugin
+    if file_path.extension().is_none() {
+        if let Some(ext) = crate::filesystem::detect_file_type_by_magic(file_path) {
+            let new_name = format!(
+                "{}.{}",
+                file_path.file_name().unwrap().to_string_lossy(),
+                ext
+            );
+            let link_path = thumbs_dir.join(&new_name);
+
+            if symlink(file_path, &link_path).is_ok() {
+                target_path_buf = link_path;
+            }
+        }
+    }
+
+    let output = Command::new("qlmanage")
+        .args(["-t", "-s", &size.to_string(), "-o"])
+        .arg(thumbs_dir)
+        .arg(&target_path_buf)
+        .output()
+        .map_err(|e| format!("Error ejecutando qlmanage: {}", e))?;
+
+    if !output.status.success() {
+        return Err("No se pudo generar la miniatura".to_string());
+    }
+
+    let file_name = target_path_buf
+        .file_name()
+        .ok_or("Nombre de archivo inválido")?
+        .to_string_lossy();
+
+    let thumb_filename = format!("{}.png", file_name);
+    let thumb_path = thumbs_dir.join(&thumb_filename);
+
+    if thumb_path.exists() {
+        let data = fs::read(&thumb_path).map_err(|e| e.to_string())?;
+        return image::load_from_memory(&data)
+            .map_err(|e| format!("Error decodificando imagen: {}", e));
+    }
+
+    Err("Miniatura no generada en la ruta esperada".to_string())
+}
@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageFormat};
+
+use super::ThumbnailBackend;
+use crate::tiff::TiffReader;
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+const TAG_COMPRESSION: u16 = 259;
+const TAG_PHOTOMETRIC: u16 = 262;
+const TAG_STRIP_OFFSETS: u16 = 273;
+const TAG_ORIENTATION: u16 = 274;
+const TAG_STRIP_BYTE_COUNTS: u16 = 279;
+const TAG_SUB_IFDS: u16 = 330;
+const TAG_JPEG_OFFSET: u16 = 513;
+const TAG_JPEG_LENGTH: u16 = 514;
+
+/// Compresión JPEG "antigua" (6) y JPEG (7).
+const COMPRESSION_JPEG: &[u32] = &[6, 7];
+/// Interpretaciones fotométricas de datos RAW (CFA y LinearRaw): nunca son vistas previas.
+const PHOTOMETRIC_RAW: &[u32] = &[32803, 34892];
+
+/// Límite de seguridad frente a archivos corruptos.
+const MAX_IFDS: usize = 64;
+
+// ==========================================
+// BACKEND
+// ==========================================
+
+/// Vista previa JPEG embebida en RAW basados en TIFF (DNG, CR2, NEF, ARW...).
+pub struct RawPreviewBackend;
+
+impl ThumbnailBackend for RawPreviewBackend {
+    fn name(&self) -> &'static str {
+        "raw"
+    }
+
+    fn supports(&self, kind: &str) -> bool {
+        matches!(kind, "raw" | "dng" | "cr2" | "nef" | "arw")
+    }
+
+    fn generate(&self, path: &Path, _size: u32) -> Result<DynamicImage, String> {
+        let file = fs::File::open(path).map_err(|e| e.to_string())?;
+        let mut tiff = TiffReader::new(BufReader::new(file))?;
+
+        let PreviewScan {
+            mut candidates,
+            orientation,
+        } = collect_previews(&mut tiff);
+        if candidates.is_empty() {
+            return Err("El archivo no contiene vistas previas JPEG".to_string());
+        }
+
+        // La vista previa más grande primero; si no decodifica, probar la siguiente
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.1));
+        for (offset, len) in candidates {
+            let Ok(data) = tiff.read_bytes(offset, len) else {
+                continue;
+            };
+            if !is_decodable_jpeg(&data) {
+                continue;
+            }
+            if let Ok(mut img) = image::load_from_memory_with_format(&data, ImageFormat::Jpeg) {
+                if let Some(o) = orientation.and_then(Orientation::from_exif) {
+                    img.apply_orientation(o);
+                }
+                return Ok(img);
+            }
+        }
+
+        Err("No se pudo decodificar ninguna vista previa embebida".to_string())
+    }
+}
+
+// ==========================================
+// RECORRIDO DE IFDs
+// ==========================================
+
+/// Resultado del recorrido de IFDs: JPEGs candidatos (offset, longitud)
+/// y la orientación EXIF declarada en IFD0.
+struct PreviewScan {
+    candidates: Vec<(u64, u64)>,
+    orientation: Option<u8>,
+}
+
+/// Recorre IFD0, la cadena de IFDs siguientes y los SubIFDs.
+fn collect_previews<R: Read + Seek>(tiff: &mut TiffReader<R>) -> PreviewScan {
+    let mut candidates = Vec::new();
+    let mut orientation = None;
+    let mut pending = vec![tiff.first_ifd()];
+    let mut visited = HashSet::new();
+
+    while let Some(offset) = pending.pop() {
+        if offset == 0 || !visited.insert(offset) || visited.len() > MAX_IFDS {
+            continue;
+        }
+        let Ok((entries, next)) = tiff.read_ifd(offset) else {
+            continue;
+        };
+        pending.push(next);
+
+        let value = |tag: u16, this: &mut TiffReader<R>| -> Option<Vec<u32>> {
+            entries
+                .iter()
+                .find(|e| e.tag == tag)
+                .and_then(|e| this.values(e).ok())
+        };
+
+        if offset == tiff.first_ifd() {
+            orientation = value(TAG_ORIENTATION, tiff)
+                .and_then(|v| v.first().copied())
+                .map(|o| o as u8);
+        }
+
+        if let Some(sub_ifds) = value(TAG_SUB_IFDS, tiff) {
+            pending.extend(sub_ifds);
+        }
+
+        // JPEGInterchangeFormat / JPEGInterchangeFormatLength
+        if let (Some(off), Some(len)) = (
+            value(TAG_JPEG_OFFSET, tiff).and_then(|v| v.first().copied()),
+            value(TAG_JPEG_LENGTH, tiff).and_then(|v| v.first().copied()),
+        ) {
+            if len > 0 {
+                candidates.push((off as u64, len as u64));
+            }
+        }
+
+        // Imagen en una sola tira comprimida en JPEG (CR2 IFD0, previews de DNG)
+        let compression = value(TAG_COMPRESSION, tiff).and_then(|v| v.first().copied());
+        let photometric = value(TAG_PHOTOMETRIC, tiff).and_then(|v| v.first().copied());
+        if compression.is_some_and(|c| COMPRESSION_JPEG.contains(&c))
+            && !photometric.is_some_and(|p| PHOTOMETRIC_RAW.contains(&p))
+        {
+            if let (Some(offsets), Some(counts)) = (
+                value(TAG_STRIP_OFFSETS, tiff),
+                value(TAG_STRIP_BYTE_COUNTS, tiff),
+            ) {
+                if offsets.len() == 1 && counts.len() == 1 && counts[0] > 0 {
+                    candidates.push((offsets[0] as u64, counts[0] as u64));
+                }
+            }
+        }
+    }
+
+    PreviewScan {
+        candidates,
+        orientation,
+    }
+}
+
+// ==========================================
+// FUNCIONES AUXILIARES
+// ==========================================
+
+/// Recorre los marcadores hasta el primer SOF y acepta solo JPEG baseline,
+/// extendido o progresivo. Los datos RAW en JPEG sin pérdida (SOF3) se descartan.
+fn is_decodable_jpeg(data: &[u8]) -> bool {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return false;
+    }
+
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return false;
+        }
+        let marker = data[pos + 1];
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        if matches!(marker, 0xC0..=0xC2) {
+            return true;
+        }
+        if (0xC3..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            return false;
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        pos += 2 + len;
+    }
+    false
+}
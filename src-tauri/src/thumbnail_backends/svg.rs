@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use image::{DynamicImage, RgbaImage};
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg::{fontdb, Options, Tree};
+
+use super::ThumbnailBackend;
+
+/// Rasterizado de SVG en proceso con `resvg`.
+pub struct SvgBackend;
+
+impl ThumbnailBackend for SvgBackend {
+    fn name(&self) -> &'static str {
+        "svg"
+    }
+
+    fn supports(&self, kind: &str) -> bool {
+        matches!(kind, "svg" | "svgz")
+    }
+
+    fn generate(&self, path: &Path, size: u32) -> Result<DynamicImage, String> {
+        let data = fs::read(path).map_err(|e| e.to_string())?;
+
+        let options = Options {
+            // Imágenes enlazadas con rutas relativas al propio SVG
+            resources_dir: path.parent().map(|p| p.to_path_buf()),
+            fontdb: system_fonts(),
+            ..Options::default()
+        };
+        let tree = Tree::from_data(&data, &options).map_err(|e| format!("SVG inválido: {}", e))?;
+
+        // `tree.size()` ya tiene en cuenta width/height y el viewBox
+        let svg_size = tree.size();
+        let scale = size as f32 / svg_size.width().max(svg_size.height());
+        let width = ((svg_size.width() * scale).round() as u32).max(1);
+        let height = ((svg_size.height() * scale).round() as u32).max(1);
+
+        let mut pixmap = Pixmap::new(width, height).ok_or("Tamaño de SVG inválido")?;
+        resvg::render(
+            &tree,
+            Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+
+        // tiny-skia trabaja con alfa premultiplicado; `image` espera RGBA recto
+        let pixels = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                let c = p.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect();
+        let img = RgbaImage::from_raw(width, height, pixels).ok_or("Error creando la imagen")?;
+        Ok(DynamicImage::ImageRgba8(img))
+    }
+}
+
+/// Fuentes del sistema para los textos del SVG. Cargarlas es lento, así que
+/// se hace una sola vez por proceso.
+fn system_fonts() -> Arc<fontdb::Database> {
+    static FONTS: OnceLock<Arc<fontdb::Database>> = OnceLock::new();
+
+    FONTS
+        .get_or_init(|| {
+            let mut db = fontdb::Database::new();
+            db.load_system_fonts();
+            Arc::new(db)
+        })
+        .clone()
+}
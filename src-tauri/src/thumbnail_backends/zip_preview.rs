@@ -0,0 +1,102 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use image::DynamicImage;
+use zip::ZipArchive;
+
+use super::ThumbnailBackend;
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+/// Rutas conocidas de la vista previa dentro del contenedor, en orden de preferencia.
+const CDR_PREVIEW_ENTRIES: &[&str] = &["metadata/thumbnails/thumbnail.bmp"];
+const OFFICE_PREVIEW_ENTRIES: &[&str] = &[
+    "docProps/thumbnail.jpeg",
+    "docProps/thumbnail.jpg",
+    "docProps/thumbnail.png",
+];
+
+/// Límite de tamaño descomprimido para una vista previa (evita zip bombs).
+const MAX_PREVIEW_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Vistas previas embebidas en formatos basados en ZIP (CorelDRAW X4+ y Office Open XML).
+pub struct ZipPreviewBackend;
+
+impl ThumbnailBackend for ZipPreviewBackend {
+    fn name(&self) -> &'static str {
+        "zip"
+    }
+
+    fn supports(&self, kind: &str) -> bool {
+        matches!(kind, "cdr" | "zip" | "docx" | "xlsx" | "pptx")
+    }
+
+    fn generate(&self, path: &Path, _size: u32) -> Result<DynamicImage, String> {
+        let file = fs::File::open(path).map_err(|e| e.to_string())?;
+        // Los CDR anteriores a X4 son RIFF y no se pueden abrir como ZIP
+        let mut archive =
+            ZipArchive::new(file).map_err(|e| format!("No es un archivo ZIP válido: {}", e))?;
+
+        let known: &[&str] = match super::file_kind(path).as_str() {
+            "docx" | "xlsx" | "pptx" => OFFICE_PREVIEW_ENTRIES,
+            _ => CDR_PREVIEW_ENTRIES,
+        };
+
+        let entry_name = find_preview_entry(&archive, known)
+            .ok_or("El archivo no contiene vista previa embebida")?;
+        let data = read_entry(&mut archive, &entry_name)?;
+
+        image::load_from_memory(&data).map_err(|e| format!("Error decodificando imagen: {}", e))
+    }
+}
+
+// ==========================================
+// FUNCIONES AUXILIARES
+// ==========================================
+
+fn find_preview_entry<R: Read + std::io::Seek>(
+    archive: &ZipArchive<R>,
+    known: &[&str],
+) -> Option<String> {
+    let names: Vec<&str> = archive.file_names().collect();
+
+    // 1. Rutas conocidas (comparación sin mayúsculas: Corel cambia el caso entre versiones)
+    for wanted in known {
+        if let Some(name) = names.iter().find(|n| n.eq_ignore_ascii_case(wanted)) {
+            return Some(name.to_string());
+        }
+    }
+
+    // 2. Cualquier imagen cuyo nombre sugiera una miniatura
+    names
+        .iter()
+        .find(|n| {
+            let lower = n.to_lowercase();
+            (lower.ends_with(".png")
+                || lower.ends_with(".bmp")
+                || lower.ends_with(".jpg")
+                || lower.ends_with(".jpeg"))
+                && (lower.contains("thumb") || lower.contains("preview"))
+        })
+        .map(|n| n.to_string())
+}
+
+fn read_entry<R: Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Vec<u8>, String> {
+    let entry = archive.by_name(name).map_err(|e| e.to_string())?;
+    if entry.size() > MAX_PREVIEW_BYTES {
+        return Err("Vista previa embebida demasiado grande".to_string());
+    }
+
+    let mut data = Vec::with_capacity(entry.size() as usize);
+    entry
+        .take(MAX_PREVIEW_BYTES)
+        .read_to_end(&mut data)
+        .map_err(|e| e.to_string())?;
+    Ok(data)
+}
@@ -1,18 +1,29 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
 use std::fs;
-use std::io::{Cursor, Read};
-use std::os::unix::fs::symlink;
-use std::path::Path;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-
-use base64::{engine::general_purpose, Engine as _};
-use image::ImageFormat;
-use serde::Serialize;
-use sha2::{Digest, Sha256};
-use std::sync::atomic::{AtomicBool, Ordering};
-use tauri::{Emitter, State};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{Emitter, Manager, State};
 use walkdir::WalkDir;
 
-use crate::paths::{get_cache_dir, get_cached_path, get_user_data_dir};
+use crate::cache_index;
+use crate::error::ArchiError;
+use crate::paths::{
+    get_cache_base_dir, get_cache_dir, get_cached_path, get_config, get_user_data_dir,
+    is_config_file,
+};
+use crate::similarity::dhash;
+use crate::thumbnail_backends;
+use crate::validation::{library_path, resolve_path};
 
 // ==========================================
 // STATE
@@ -20,6 +31,17 @@ use crate::paths::{get_cache_dir, get_cached_path, get_user_data_dir};
 
 pub struct CacheCancellation {
     pub canceled: AtomicBool,
+    /// Ocupado mientras trabaja el grupo de `generate_missing_thumbnails`.
+    pub running: Mutex<()>,
+}
+
+impl CacheCancellation {
+    /// Cancela la generación en curso y espera a que termine. Mientras se
+    /// conserve el guard no empieza otra.
+    pub fn stop(&self) -> MutexGuard<'_, ()> {
+        self.canceled.store(true, Ordering::Relaxed);
+        self.running.lock().unwrap_or_else(|e| e.into_inner())
+    }
 }
 
 // ==========================================
@@ -28,50 +50,138 @@ pub struct CacheCancellation {
 
 const VALID_EXTENSIONS: &[&str] = &[
     "jpg", "jpeg", "png", "webp", "bmp", "psd", "psb", "ai", "indd", "pdf", "eps", "tiff", "tif",
-    "raw", "dng", "cdr", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "gif", "svg", "ico",
+    "raw", "dng", "cr2", "nef", "arw", "cdr", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "gif",
+    "svg", "ico",
 ];
 
 // ==========================================
-// HELPERS
+// TAMAÑOS Y MODOS
 // ==========================================
 
-fn convert_to_webp(data: &[u8]) -> Result<Vec<u8>, String> {
-    let img =
-        image::load_from_memory(data).map_err(|e| format!("Error decodificando imagen: {}", e))?;
-    let mut buf = Vec::new();
-    img.write_to(&mut Cursor::new(&mut buf), ImageFormat::WebP)
-        .map_err(|e| format!("Error codificando a WebP: {}", e))?;
-    Ok(buf)
+/// Clase de tamaño de la miniatura. Cada clase tiene su propia entrada en caché.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailSize {
+    /// Cuadrícula de archivos.
+    #[default]
+    Small,
+    /// Vista previa ampliada.
+    Large,
+}
+
+impl ThumbnailSize {
+    /// Lado mayor en píxeles.
+    fn pixels(self) -> u32 {
+        match self {
+            ThumbnailSize::Small => 400,
+            ThumbnailSize::Large => 1600,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            ThumbnailSize::Small => "s",
+            ThumbnailSize::Large => "l",
+        }
+    }
+}
+
+/// `fit` conserva la imagen completa; `fill` recorta al centro en un cuadrado.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailMode {
+    Fit,
+    Fill,
+}
+
+impl ThumbnailMode {
+    /// Modo guardado en `AppConfig.thumbnail_mode` (cualquier valor distinto de "fill" es "fit").
+    fn from_config() -> Self {
+        if get_config().thumbnail_mode.eq_ignore_ascii_case("fill") {
+            ThumbnailMode::Fill
+        } else {
+            ThumbnailMode::Fit
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            ThumbnailMode::Fit => "fit",
+            ThumbnailMode::Fill => "fill",
+        }
+    }
+}
+
+/// Variante dentro de una entrada de caché: `{tamaño}-{modo}`.
+fn variant_key(size: ThumbnailSize, mode: ThumbnailMode) -> String {
+    format!("{}-{}", size.key(), mode.key())
 }
 
-fn calculate_hash(path: &Path) -> String {
-    let mut hasher = Sha256::new();
-    let path_str = path.to_string_lossy().to_string();
-    hasher.update(path_str.as_bytes());
+/// Nombre del archivo en caché: `{huella}-{tamaño}-{modo}`.
+fn cache_key(fingerprint: &str, size: ThumbnailSize, mode: ThumbnailMode) -> String {
+    format!("{}-{}", fingerprint, variant_key(size, mode))
+}
 
-    if let Ok(metadata) = fs::metadata(path) {
-        if let Ok(modified) = metadata.modified() {
-            if let Ok(duration) = modified.duration_since(std::time::SystemTime::UNIX_EPOCH) {
-                hasher.update(duration.as_secs().to_le_bytes());
+/// Escala la imagen al tamaño pedido sin ampliarla nunca.
+fn resize_thumbnail(img: DynamicImage, size: ThumbnailSize, mode: ThumbnailMode) -> DynamicImage {
+    let side = size.pixels();
+    let (width, height) = (img.width(), img.height());
+
+    match mode {
+        ThumbnailMode::Fit => {
+            if width <= side && height <= side {
+                img
+            } else {
+                img.resize(side, side, FilterType::Lanczos3)
+            }
+        }
+        ThumbnailMode::Fill => {
+            let side = side.min(width).min(height);
+            if width == side && height == side {
+                img
+            } else {
+                img.resize_to_fill(side, side, FilterType::Lanczos3)
             }
         }
     }
-    format!("{:x}", hasher.finalize())
 }
 
-fn save_and_return_webp(data: &[u8], cache_path: &Path) -> Result<String, String> {
-    let webp_data = convert_to_webp(data)?;
+// ==========================================
+// HELPERS
+// ==========================================
+
+fn convert_to_webp(img: &DynamicImage) -> Result<Vec<u8>, String> {
+    let mut buf = Vec::new();
+    // El codificador WebP solo acepta RGB8/RGBA8 (p. ej. TIFF de 16 bits llega como Rgb16)
+    let result = match img {
+        DynamicImage::ImageRgb8(_) | DynamicImage::ImageRgba8(_) => {
+            img.write_to(&mut Cursor::new(&mut buf), ImageFormat::WebP)
+        }
+        _ => DynamicImage::ImageRgba8(img.to_rgba8())
+            .write_to(&mut Cursor::new(&mut buf), ImageFormat::WebP),
+    };
+    result.map_err(|e| format!("Error codificando a WebP: {}", e))?;
+    Ok(buf)
+}
+
+fn save_webp(img: &DynamicImage, cache_path: &Path) -> Result<Vec<u8>, String> {
+    let webp_data = convert_to_webp(img)?;
     let _ = fs::write(cache_path, &webp_data);
-    let b64 = general_purpose::STANDARD.encode(&webp_data);
-    Ok(format!("data:image/webp;base64,{}", b64))
+    Ok(webp_data)
 }
 
-fn get_candidates() -> Vec<std::path::PathBuf> {
+pub(crate) fn get_candidates() -> Vec<std::path::PathBuf> {
     let user_data_dir = get_user_data_dir();
+    // Fuera de macOS las cachés de todas las bibliotecas y config.json viven
+    // dentro de la biblioteca por defecto
+    let cache_base = get_cache_base_dir();
     let mut candidates = Vec::new();
     let walker = WalkDir::new(&user_data_dir).into_iter().filter_entry(|e| {
         let name = e.file_name().to_string_lossy();
-        !name.starts_with('.') && name != "node_modules"
+        !name.starts_with('.')
+            && name != "node_modules"
+            && !e.path().starts_with(&cache_base)
+            && !is_config_file(e.path())
     });
 
     for entry in walker.flatten() {
@@ -103,21 +213,17 @@ pub fn cache_cleanup() {
             return;
         }
 
-        // 1. Recolectar hashes válidos
-        let mut valid_hashes = std::collections::HashSet::new();
-        let candidates = get_candidates();
+        // 1. Sincronizar el índice con la biblioteca (las rutas nuevas o
+        //    modificadas son las únicas que se vuelven a leer)
+        let keep = cache_index::prune(&get_candidates());
 
-        for path in candidates {
-            valid_hashes.insert(calculate_hash(&path));
-        }
-
-        // 2. Borrar huérfanos
+        // 2. Borrar miniaturas cuyo contenido ya no existe en la biblioteca
         if let Ok(entries) = fs::read_dir(&cache_dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                if path.is_file() {
+                if path.is_file() && path.extension().is_some_and(|e| e == "webp") {
                     if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                        if !valid_hashes.contains(stem) {
+                        if !keep.contains(stem) {
                             let _ = fs::remove_file(path);
                         }
                     }
@@ -136,63 +242,32 @@ pub struct CacheStats {
     pub file_count: usize,
     pub total_size: u64,
     pub missing_count: usize,
+    /// Tamaño máximo configurado en bytes (0 = sin límite).
+    pub max_size: u64,
+    /// Miniaturas desalojadas por el límite de tamaño.
+    pub eviction_count: u64,
 }
 
 #[tauri::command]
-pub async fn get_cache_stats() -> Result<CacheStats, String> {
-    let cache_dir = get_cache_dir();
-
-    // Calcular estadísticas de archivos existentes en caché
-    let mut file_count: usize = 0;
-    let mut total_size: u64 = 0;
-
-    if cache_dir.exists() {
-        if let Ok(entries) = fs::read_dir(&cache_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_file() {
-                    file_count += 1;
-                    if let Ok(metadata) = fs::metadata(&path) {
-                        total_size += metadata.len();
-                    }
-                } else if path.is_dir() {
-                    // Contar recursivamente si hay subdirectorios (aunque el cache suele ser plano)
-                    for sub in WalkDir::new(&path).into_iter().flatten() {
-                        let p = sub.path();
-                        if p.is_file() {
-                            file_count += 1;
-                            if let Ok(metadata) = fs::metadata(p) {
-                                total_size += metadata.len();
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
+pub async fn get_cache_stats() -> Result<CacheStats, ArchiError> {
+    // Estadísticas de la caché según el índice, sin recorrer la biblioteca
+    let index_stats = cache_index::stats();
 
-    // Calcular archivos faltantes
-    let candidates = get_candidates();
-    let mut missing_count = 0;
-
-    // Optimización: podríamos paralelizar esto si fuera muy lento, pero por ahora iteramos
-    for path in candidates {
-        let hash = calculate_hash(&path);
-        let cache_path = get_cached_path(&hash);
-        if !cache_path.exists() {
-            missing_count += 1;
-        }
-    }
+    // Archivos sin la variante de cuadrícula con el modo configurado
+    let mode = ThumbnailMode::from_config();
+    let missing_count = cache_index::missing_count(&variant_key(ThumbnailSize::Small, mode));
 
     Ok(CacheStats {
-        file_count,
-        total_size,
+        file_count: index_stats.file_count,
+        total_size: index_stats.total_size,
         missing_count,
+        max_size: get_config().cache_max_size_mb * 1024 * 1024,
+        eviction_count: index_stats.evictions,
     })
 }
 
 #[tauri::command]
-pub async fn open_cache_folder() -> Result<(), String> {
+pub async fn open_cache_folder() -> Result<(), ArchiError> {
     let cache_dir = get_cache_dir();
 
     if !cache_dir.exists() {
@@ -219,7 +294,7 @@ pub async fn open_cache_folder() -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn clear_all_cache() -> Result<(), String> {
+pub async fn clear_all_cache() -> Result<(), ArchiError> {
     let cache_dir = get_cache_dir();
 
     if !cache_dir.exists() {
@@ -242,6 +317,8 @@ pub async fn clear_all_cache() -> Result<(), String> {
         }
     }
 
+    cache_index::clear_entries();
+
     Ok(())
 }
 
@@ -253,16 +330,19 @@ struct CacheProgress {
 }
 
 #[tauri::command]
-pub fn cancel_cache_generation(state: State<CacheCancellation>) -> Result<(), String> {
+pub fn cancel_cache_generation(state: State<CacheCancellation>) -> Result<(), ArchiError> {
     state.canceled.store(true, Ordering::Relaxed);
     Ok(())
 }
 
+/// Genera en segundo plano las miniaturas de cuadrícula que faltan en toda la
+/// biblioteca. `priority_folder` (carpeta abierta en Biblioteca) se procesa primero.
 #[tauri::command]
 pub async fn generate_missing_thumbnails(
     window: tauri::WebviewWindow,
     state: State<'_, CacheCancellation>,
-) -> Result<String, String> {
+    priority_folder: Option<String>,
+) -> Result<String, ArchiError> {
     let cache_dir = get_cache_dir();
 
     // Resetear estado de cancelación
@@ -272,11 +352,140 @@ pub async fn generate_missing_thumbnails(
         fs::create_dir_all(&cache_dir).map_err(|e| e.to_string())?;
     }
 
-    // 1. Recolectar candidatos
-    let candidates = get_candidates();
+    let priority_dir = priority_folder
+        .map(|f| resolve_path(&f, "Biblioteca"))
+        .transpose()?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = window.state::<CacheCancellation>();
+        let _running = state.running.lock().unwrap_or_else(|e| e.into_inner());
+        run_generation_pool(&window, &state.canceled, priority_dir.as_deref())
+    })
+    .await
+    .map_err(ArchiError::from)
+}
+
+#[tauri::command]
+pub async fn generate_thumbnail(
+    path: String,
+    size: Option<ThumbnailSize>,
+    mode: Option<ThumbnailMode>,
+) -> Result<String, ArchiError> {
+    let file_path = Path::new(&path);
+    if !file_path.exists() {
+        return Err(ArchiError::NotFound(file_path.to_path_buf()));
+    }
+    // Solo archivos dentro de la carpeta de ARCHI
+    let file_path = &library_path(&path)?;
+
+    let cache_dir = get_cache_dir();
+    if !cache_dir.exists() {
+        let _ = fs::create_dir_all(&cache_dir);
+    }
+
+    let source = ThumbnailSource {
+        path: file_path.to_path_buf(),
+        size: size.unwrap_or_default(),
+        mode: mode.unwrap_or_else(ThumbnailMode::from_config),
+    };
+    let fingerprint = cache_index::fingerprint(file_path)?;
+    let key = cache_key(&fingerprint, source.size, source.mode);
+
+    // 1. Generar si no está en caché, para que el frontend reciba el error si falla
+    if get_cached_path(&key).exists() {
+        cache_index::touch(&fingerprint, &variant_key(source.size, source.mode));
+    } else {
+        render_thumbnail(&source, &fingerprint)?;
+        schedule_eviction();
+    }
+
+    // 2. El protocolo puede regenerarla si la caché se borra mientras la vista sigue abierta
+    if let Ok(mut sources) = thumbnail_sources().lock() {
+        sources.remember(key.clone(), source);
+    }
+
+    Ok(thumbnail_url(&key))
+}
+
+/// Miniatura de cuadrícula en modo `fit` ya decodificada, generándola si falta.
+/// Es la base de los análisis de imagen (paleta, similitud...). Devuelve
+/// también la huella del archivo para guardar los resultados en el índice.
+pub(crate) fn load_thumbnail(path: &Path) -> Result<(String, DynamicImage), String> {
+    let source = ThumbnailSource {
+        path: path.to_path_buf(),
+        size: ThumbnailSize::Small,
+        mode: ThumbnailMode::Fit,
+    };
+    let fingerprint = cache_index::fingerprint(path)?;
+    let cache_path = get_cached_path(&cache_key(&fingerprint, source.size, source.mode));
+
+    let data = match fs::read(&cache_path) {
+        Ok(data) => {
+            cache_index::touch(&fingerprint, &variant_key(source.size, source.mode));
+            data
+        }
+        Err(_) => {
+            let _ = fs::create_dir_all(get_cache_dir());
+            let data = render_thumbnail(&source, &fingerprint)?;
+            schedule_eviction();
+            data
+        }
+    };
+
+    let img = image::load_from_memory_with_format(&data, ImageFormat::WebP)
+        .map_err(|e| format!("Error leyendo la miniatura: {}", e))?;
+    Ok((fingerprint, img))
+}
+
+// ==========================================
+// POOL DE GENERACIÓN
+// ==========================================
 
+/// Máximo de miniaturas generándose a la vez (cada decodificación de PSD o RAW
+/// puede ocupar cientos de MB).
+const MAX_WORKERS: usize = 4;
+
+/// Intervalo mínimo entre eventos `cache-progress`.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Entrada de la cola: menor prioridad primero y, a igualdad, orden de recorrido.
+type Job = Reverse<(u8, usize)>;
+
+fn worker_count() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get().saturating_sub(1))
+        .unwrap_or(1)
+        .clamp(1, MAX_WORKERS)
+}
+
+/// 0 = dentro de la carpeta abierta, 1 = en sus subcarpetas, 2 = resto de la biblioteca.
+fn job_priority(path: &Path, priority_dir: Option<&Path>) -> u8 {
+    match priority_dir {
+        Some(dir) if path.parent() == Some(dir) => 0,
+        Some(dir) if path.starts_with(dir) => 1,
+        _ => 2,
+    }
+}
+
+fn run_generation_pool(
+    window: &tauri::WebviewWindow,
+    canceled: &AtomicBool,
+    priority_dir: Option<&Path>,
+) -> String {
+    let candidates = get_candidates();
+    let mode = ThumbnailMode::from_config();
     let total = candidates.len();
-    let mut generated_count = 0;
+
+    let queue: BinaryHeap<Job> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, path)| Reverse((job_priority(path, priority_dir), i)))
+        .collect();
+    let queue = Mutex::new(queue);
+
+    let processed = AtomicUsize::new(0);
+    let generated = AtomicUsize::new(0);
+    let last_emit = Mutex::new(Instant::now());
 
     // Notificar inicio
     let _ = window.emit(
@@ -288,43 +497,67 @@ pub async fn generate_missing_thumbnails(
         },
     );
 
-    for (i, path) in candidates.iter().enumerate() {
-        if state.canceled.load(Ordering::Relaxed) {
-            let _ = window.emit(
-                "cache-progress",
-                CacheProgress {
-                    current: i,
-                    total,
-                    status: "Cancelado".to_string(),
-                },
-            );
-            return Ok(format!(
-                "Generación cancelada. Se generaron {} miniaturas.",
-                generated_count
-            ));
+    std::thread::scope(|scope| {
+        for _ in 0..worker_count() {
+            scope.spawn(|| loop {
+                if canceled.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Some(Reverse((_, i))) = queue.lock().ok().and_then(|mut q| q.pop()) else {
+                    break;
+                };
+
+                let source = ThumbnailSource {
+                    path: candidates[i].clone(),
+                    size: ThumbnailSize::Small,
+                    mode,
+                };
+                if let Ok(fingerprint) = cache_index::fingerprint(&source.path) {
+                    let cache_path = get_cached_path(&cache_key(&fingerprint, source.size, mode));
+                    if !cache_path.exists() && render_thumbnail(&source, &fingerprint).is_ok() {
+                        generated.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+
+                // Notificar progreso como mucho cada PROGRESS_INTERVAL
+                let current = processed.fetch_add(1, Ordering::Relaxed) + 1;
+                if let Ok(mut last) = last_emit.try_lock() {
+                    if last.elapsed() >= PROGRESS_INTERVAL {
+                        *last = Instant::now();
+                        let _ = window.emit(
+                            "cache-progress",
+                            CacheProgress {
+                                current,
+                                total,
+                                status: "Generando caché...".to_string(),
+                            },
+                        );
+                    }
+                }
+            });
         }
+    });
 
-        let hash = calculate_hash(path);
-        let cache_path = get_cached_path(&hash);
+    let processed = processed.into_inner();
+    let generated = generated.into_inner();
+    cache_index::flush();
+    if generated > 0 {
+        schedule_eviction();
+    }
 
-        // Notificar progreso actual
+    if canceled.load(Ordering::Relaxed) {
         let _ = window.emit(
             "cache-progress",
             CacheProgress {
-                current: i + 1,
+                current: processed,
                 total,
-                status: "Generando caché...".to_string(),
+                status: "Cancelado".to_string(),
             },
         );
-
-        if !cache_path.exists() {
-            if generate_thumbnail(path.to_string_lossy().to_string())
-                .await
-                .is_ok()
-            {
-                generated_count += 1;
-            }
-        }
+        return format!(
+            "Generación cancelada. Se generaron {} miniaturas.",
+            generated
+        );
     }
 
     // Notificar finalización
@@ -337,177 +570,196 @@ pub async fn generate_missing_thumbnails(
         },
     );
 
-    Ok(format!(
-        "Se generaron {} miniaturas nuevas",
-        generated_count
-    ))
+    format!("Se generaron {} miniaturas nuevas", generated)
 }
 
-#[tauri::command]
-pub async fn generate_thumbnail(path: String) -> Result<String, String> {
-    let file_path = Path::new(&path);
-    if !file_path.exists() {
-        return Err("Archivo no encontrado".to_string());
-    }
+// ==========================================
+// GENERATION STRATEGIES
+// ==========================================
 
-    let cache_dir = get_cache_dir();
-    if !cache_dir.exists() {
-        let _ = fs::create_dir_all(&cache_dir);
+/// Genera la miniatura con los backends, la escala, la guarda en caché y la
+/// registra en el índice junto al hash perceptual de la imagen.
+fn render_thumbnail(source: &ThumbnailSource, fingerprint: &str) -> Result<Vec<u8>, String> {
+    let img = generate_with_backends(&source.path, source.size.pixels())?;
+    if cache_index::phash(fingerprint).is_none() {
+        // Sobre la imagen completa: en modo `fill` la miniatura está recortada
+        cache_index::record_phash(fingerprint, dhash(&img));
     }
+    let variant = variant_key(source.size, source.mode);
+    let cache_path = get_cached_path(&cache_key(fingerprint, source.size, source.mode));
+
+    let data = save_webp(
+        &resize_thumbnail(img, source.size, source.mode),
+        &cache_path,
+    )?;
+    cache_index::record_variant(fingerprint, &variant, data.len() as u64);
+    Ok(data)
+}
 
-    let hash = calculate_hash(file_path);
-    let cache_path = get_cached_path(&hash);
+/// Imagen completa (sin recortar) a tamaño de cuadrícula, la misma que usa
+/// `render_thumbnail` para el hash perceptual.
+pub(crate) fn decode_image(path: &Path) -> Result<DynamicImage, String> {
+    generate_with_backends(path, ThumbnailSize::Small.pixels())
+}
 
-    // 1. Verificar caché existente
-    if cache_path.exists() {
-        if let Ok(data) = fs::read(&cache_path) {
-            let b64 = general_purpose::STANDARD.encode(data);
-            return Ok(format!("data:image/webp;base64,{}", b64));
-        }
-    }
+fn generate_with_backends(file_path: &Path, size: u32) -> Result<DynamicImage, String> {
+    let kind = thumbnail_backends::file_kind(file_path);
+    let mut last_error = None;
 
-    // 2. Intentar extracción CDR (ZIP)
-    if let Ok(data) = try_extract_cdr(file_path) {
-        return save_and_return_webp(&data, &cache_path);
+    for backend in thumbnail_backends::registered() {
+        if !backend.supports(&kind) {
+            continue;
+        }
+        match backend.generate(file_path, size) {
+            Ok(img) => return Ok(img),
+            Err(e) => last_error = Some(format!("{}: {}", backend.name(), e)),
+        }
     }
 
-    // 3. Fallback a QuickLook (qlmanage)
-    generate_with_qlmanage(file_path, &cache_path)
+    Err(last_error
+        .unwrap_or_else(|| "No hay un generador de miniaturas para este formato".to_string()))
 }
 
 // ==========================================
-// GENERATION STRATEGIES
+// LÍMITE DE TAMAÑO (LRU)
 // ==========================================
 
-fn try_extract_cdr(file_path: &Path) -> Result<Vec<u8>, ()> {
-    let is_cdr = file_path.to_string_lossy().to_lowercase().ends_with(".cdr")
-        || (file_path.extension().is_none()
-            && fs::read(file_path)
-                .ok()
-                .map(|b| b.starts_with(b"PK\x03\x04") || b.starts_with(b"RIFF"))
-                .unwrap_or(false));
+/// Lanza en segundo plano el desalojo LRU según `AppConfig.cache_max_size_mb`.
+/// Si ya hay uno en curso no se lanza otro.
+fn schedule_eviction() {
+    static RUNNING: AtomicBool = AtomicBool::new(false);
 
-    if !is_cdr {
-        return Err(());
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return;
     }
+    std::thread::spawn(|| {
+        let max_mb = get_config().cache_max_size_mb;
+        if max_mb > 0 {
+            cache_index::evict_lru(max_mb * 1024 * 1024);
+        }
+        RUNNING.store(false, Ordering::SeqCst);
+    });
+}
 
-    let list_output = Command::new("unzip")
-        .arg("-l")
-        .arg(file_path)
-        .output()
-        .map_err(|_| ())?;
+// ==========================================
+// PROTOCOLO archi-thumb://
+// ==========================================
 
-    if !list_output.status.success() {
-        return Err(());
-    }
+pub const THUMBNAIL_SCHEME: &str = "archi-thumb";
 
-    let stdout = String::from_utf8_lossy(&list_output.stdout);
-    let mut candidate = None;
+/// Orígenes que se recuerdan para regenerar miniaturas; al superarlo se
+/// olvidan los pedidos hace más tiempo.
+const MAX_SOURCES: usize = 4096;
 
-    for line in stdout.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if let Some(name) = parts.last() {
-            let lower = name.to_lowercase();
-            if lower.ends_with(".png") || lower.ends_with(".bmp") || lower.ends_with(".jpg") {
-                if lower.contains("thumb") || lower.contains("preview") {
-                    candidate = Some(name.to_string());
-                    break;
-                }
-                if candidate.is_none() {
-                    candidate = Some(name.to_string());
-                }
-            }
-        }
-    }
+/// Origen de una miniatura ya solicitada, necesario para regenerarla.
+#[derive(Clone)]
+struct ThumbnailSource {
+    path: PathBuf,
+    size: ThumbnailSize,
+    mode: ThumbnailMode,
+}
 
-    if let Some(target) = candidate {
-        let output = Command::new("unzip")
-            .arg("-p")
-            .arg(file_path)
-            .arg(&target)
-            .output()
-            .map_err(|_| ())?;
+/// Clave de caché → origen, en orden de llegada.
+#[derive(Default)]
+struct ThumbnailSources {
+    by_key: HashMap<String, ThumbnailSource>,
+    order: VecDeque<String>,
+}
 
-        if output.status.success() && !output.stdout.is_empty() {
-            return Ok(output.stdout);
+impl ThumbnailSources {
+    fn remember(&mut self, key: String, source: ThumbnailSource) {
+        if self.by_key.insert(key.clone(), source).is_none() {
+            self.order.push_back(key);
+        }
+        while self.by_key.len() > MAX_SOURCES {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.by_key.remove(&oldest);
         }
     }
 
-    Err(())
+    fn forget(&mut self, key: &str) {
+        if self.by_key.remove(key).is_some() {
+            self.order.retain(|k| k != key);
+        }
+    }
 }
 
-fn generate_with_qlmanage(file_path: &Path, cache_path: &Path) -> Result<String, String> {
-    let temp_dir = std::env::temp_dir();
-    let thumbs_dir = temp_dir.join("archi_thumbs");
+fn thumbnail_sources() -> &'static Mutex<ThumbnailSources> {
+    static SOURCES: OnceLock<Mutex<ThumbnailSources>> = OnceLock::new();
+    SOURCES.get_or_init(|| Mutex::new(ThumbnailSources::default()))
+}
 
-    if !thumbs_dir.exists() {
-        fs::create_dir_all(&thumbs_dir).map_err(|e| e.to_string())?;
+/// URL del protocolo para una clave de caché. Windows y Android no admiten
+/// esquemas propios en el webview y Tauri los expone como `http://<esquema>.localhost`.
+fn thumbnail_url(key: &str) -> String {
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost/{}", THUMBNAIL_SCHEME, key)
+    } else {
+        format!("{}://localhost/{}", THUMBNAIL_SCHEME, key)
     }
+}
 
-    let mut target_path_buf = file_path.to_path_buf();
-    let mut is_symlink = false;
-
-    // Manejo de archivos sin extensión
-    if file_path.extension().is_none() {
-        if let Ok(mut file) = fs::File::open(file_path) {
-            let mut buffer = [0u8; 8];
-            if file.read_exact(&mut buffer).is_ok() {
-                let ext = if buffer.starts_with(b"8BPS") {
-                    Some("psd")
-                } else if buffer.starts_with(b"%PDF") {
-                    Some("pdf")
-                } else if buffer.starts_with(b"PK\x03\x04") {
-                    Some("zip")
-                } else if buffer.starts_with(b"RIFF") {
-                    Some("cdr")
-                } else {
-                    None
-                };
+/// Sirve `archi-thumb://localhost/<clave>` desde la caché, regenerando la
+/// miniatura si se borró, su origen es conocido y su contenido no cambió.
+pub fn handle_thumbnail_request(request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let key = request.uri().path().trim_start_matches('/');
 
-                if let Some(e) = ext {
-                    let new_name =
-                        format!("{}.{}", file_path.file_name().unwrap().to_string_lossy(), e);
-                    let link_path = thumbs_dir.join(&new_name);
-                    let _ = fs::remove_file(&link_path);
+    // Las claves son `{huella}-{tamaño}-{modo}`: nada que permita salir de la caché
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return empty_response(StatusCode::BAD_REQUEST);
+    }
 
-                    if symlink(file_path, &link_path).is_ok() {
-                        target_path_buf = link_path;
-                        is_symlink = true;
-                    }
+    let (fingerprint, variant) = key.split_once('-').unwrap_or((key, ""));
+    let data = match fs::read(get_cached_path(key)) {
+        Ok(data) => {
+            cache_index::touch(fingerprint, variant);
+            data
+        }
+        Err(_) => {
+            let source = thumbnail_sources()
+                .lock()
+                .ok()
+                .and_then(|sources| sources.by_key.get(key).cloned());
+            // Si el archivo cambió, la clave ya no corresponde a su contenido
+            let source = source.filter(|source| {
+                let current = cache_index::fingerprint(&source.path);
+                if current.as_deref() == Ok(fingerprint) {
+                    return true;
+                }
+                if let Ok(mut sources) = thumbnail_sources().lock() {
+                    sources.forget(key);
                 }
+                false
+            });
+            let rendered = source.and_then(|source| {
+                let _ = fs::create_dir_all(get_cache_dir());
+                render_thumbnail(&source, fingerprint).ok()
+            });
+            match rendered {
+                Some(data) => data,
+                None => return empty_response(StatusCode::NOT_FOUND),
             }
         }
-    }
-
-    let output = Command::new("qlmanage")
-        .args(&["-t", "-s", "400", "-o"])
-        .arg(&thumbs_dir)
-        .arg(&target_path_buf)
-        .output()
-        .map_err(|e| format!("Error ejecutando qlmanage: {}", e))?;
-
-    if is_symlink {
-        let _ = fs::remove_file(&target_path_buf);
-    }
-
-    if !output.status.success() {
-        return Err("No se pudo generar la miniatura".to_string());
-    }
-
-    let file_name = target_path_buf
-        .file_name()
-        .ok_or("Nombre de archivo inválido")?
-        .to_string_lossy();
-
-    let thumb_filename = format!("{}.png", file_name);
-    let thumb_path = thumbs_dir.join(&thumb_filename);
-
-    if thumb_path.exists() {
-        let data = fs::read(&thumb_path).map_err(|e| e.to_string())?;
-        let _ = fs::remove_file(thumb_path);
-
-        return save_and_return_webp(&data, cache_path);
-    }
+    };
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, "image/webp")
+        .header(header::CONTENT_LENGTH, data.len())
+        // La clave es la huella del contenido: una misma URL nunca cambia de imagen
+        .header(header::CACHE_CONTROL, "public, max-age=31536000, immutable")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(data)
+        .unwrap_or_else(|_| empty_response(StatusCode::INTERNAL_SERVER_ERROR))
+}
 
-    Err("Miniatura no generada en la ruta esperada".to_string())
+fn empty_response(status: StatusCode) -> Response<Vec<u8>> {
+    let mut response = Response::new(Vec::new());
+    *response.status_mut() = status;
+    response
 }
@@ -0,0 +1,181 @@
+use std::io::{Read, Seek, SeekFrom};
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+/// Límites de seguridad frente a archivos corruptos.
+const MAX_IFD_ENTRIES: u16 = 4096;
+const MAX_BLOCK_BYTES: u64 = 64 * 1024 * 1024;
+
+// ==========================================
+// LECTOR TIFF
+// ==========================================
+
+/// Entrada de un IFD tal como está en disco. `raw` contiene el valor si cabe
+/// en 4 bytes o, si no, el offset donde está.
+pub(crate) struct Entry {
+    pub tag: u16,
+    pub field_type: u16,
+    pub count: u32,
+    pub raw: [u8; 4],
+}
+
+/// Lector mínimo de la estructura TIFF (usada también por EXIF y la mayoría de RAW).
+pub(crate) struct TiffReader<R> {
+    r: R,
+    big_endian: bool,
+    first_ifd: u32,
+}
+
+impl<R: Read + Seek> TiffReader<R> {
+    pub fn new(mut r: R) -> Result<Self, String> {
+        let mut header = [0u8; 8];
+        r.read_exact(&mut header).map_err(|e| e.to_string())?;
+
+        let big_endian = match &header[..2] {
+            b"II" => false,
+            b"MM" => true,
+            _ => return Err("No es un archivo TIFF/RAW".to_string()),
+        };
+
+        let mut reader = Self {
+            r,
+            big_endian,
+            first_ifd: 0,
+        };
+
+        // 42 = TIFF estándar; RW2 (0x55) y ORF (RO/SR) usan variantes con la misma estructura
+        let magic = reader.u16_from(&header[2..4]);
+        if !matches!(magic, 42 | 0x55 | 0x4F52 | 0x5352) {
+            return Err("Cabecera TIFF inválida".to_string());
+        }
+        reader.first_ifd = reader.u32_from(&header[4..8]);
+        Ok(reader)
+    }
+
+    pub fn first_ifd(&self) -> u32 {
+        self.first_ifd
+    }
+
+    /// Entradas del IFD en `offset` y offset del siguiente IFD (0 si no hay).
+    pub fn read_ifd(&mut self, offset: u32) -> Result<(Vec<Entry>, u32), String> {
+        self.r
+            .seek(SeekFrom::Start(offset as u64))
+            .map_err(|e| e.to_string())?;
+
+        let count = self.read_u16()?;
+        if count > MAX_IFD_ENTRIES {
+            return Err("IFD corrupto".to_string());
+        }
+
+        let mut entries = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let tag = self.read_u16()?;
+            let field_type = self.read_u16()?;
+            let count = self.read_u32()?;
+            let mut raw = [0u8; 4];
+            self.r.read_exact(&mut raw).map_err(|e| e.to_string())?;
+            entries.push(Entry {
+                tag,
+                field_type,
+                count,
+                raw,
+            });
+        }
+
+        let next = self.read_u32()?;
+        Ok((entries, next))
+    }
+
+    /// Valores enteros de una entrada SHORT, LONG o IFD.
+    pub fn values(&mut self, entry: &Entry) -> Result<Vec<u32>, String> {
+        let size = match entry.field_type {
+            3 => 2,      // SHORT
+            4 | 13 => 4, // LONG, IFD
+            _ => return Err("Tipo de campo no soportado".to_string()),
+        };
+        let bytes = self.entry_bytes(entry, size)?;
+
+        Ok(bytes
+            .chunks_exact(size)
+            .map(|c| {
+                if size == 2 {
+                    self.u16_from(c) as u32
+                } else {
+                    self.u32_from(c)
+                }
+            })
+            .collect())
+    }
+
+    /// Valores de una entrada RATIONAL (numerador / denominador).
+    pub fn rationals(&mut self, entry: &Entry) -> Result<Vec<f64>, String> {
+        if entry.field_type != 5 {
+            return Err("Tipo de campo no soportado".to_string());
+        }
+        let bytes = self.entry_bytes(entry, 8)?;
+
+        Ok(bytes
+            .chunks_exact(8)
+            .filter_map(|c| {
+                let den = self.u32_from(&c[4..]);
+                (den != 0).then(|| self.u32_from(c) as f64 / den as f64)
+            })
+            .collect())
+    }
+
+    fn entry_bytes(&mut self, entry: &Entry, size: usize) -> Result<Vec<u8>, String> {
+        let count = entry.count.min(1024) as usize;
+        let total = size * count;
+
+        if total <= 4 {
+            Ok(entry.raw[..total].to_vec())
+        } else {
+            let offset = self.u32_from(&entry.raw) as u64;
+            self.read_bytes(offset, total as u64)
+        }
+    }
+
+    pub fn read_bytes(&mut self, offset: u64, len: u64) -> Result<Vec<u8>, String> {
+        if len > MAX_BLOCK_BYTES {
+            return Err("Bloque demasiado grande".to_string());
+        }
+        self.r
+            .seek(SeekFrom::Start(offset))
+            .map_err(|e| e.to_string())?;
+        let mut buf = vec![0u8; len as usize];
+        self.r.read_exact(&mut buf).map_err(|e| e.to_string())?;
+        Ok(buf)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, String> {
+        let mut buf = [0u8; 2];
+        self.r.read_exact(&mut buf).map_err(|e| e.to_string())?;
+        Ok(self.u16_from(&buf))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        let mut buf = [0u8; 4];
+        self.r.read_exact(&mut buf).map_err(|e| e.to_string())?;
+        Ok(self.u32_from(&buf))
+    }
+
+    fn u16_from(&self, b: &[u8]) -> u16 {
+        let b = [b[0], b[1]];
+        if self.big_endian {
+            u16::from_be_bytes(b)
+        } else {
+            u16::from_le_bytes(b)
+        }
+    }
+
+    fn u32_from(&self, b: &[u8]) -> u32 {
+        let b = [b[0], b[1], b[2], b[3]];
+        if self.big_endian {
+            u32::from_be_bytes(b)
+        } else {
+            u32::from_le_bytes(b)
+        }
+    }
+}
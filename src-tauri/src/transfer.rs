@@ -0,0 +1,647 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use walkdir::WalkDir;
+
+use crate::error::ArchiError;
+use crate::journal;
+use crate::metadata_store;
+use crate::models::{TransferItem, TransferReport, TransferStatus};
+use crate::trash_bin;
+use crate::validation::{resolve_path, PathError};
+
+// ==========================================
+// STATE
+// ==========================================
+
+/// Transferencias en curso, cada una con su indicador de cancelación.
+#[derive(Default)]
+pub struct TransferJobs {
+    next_id: AtomicU64,
+    running: Mutex<HashMap<u64, Arc<AtomicBool>>>,
+}
+
+impl TransferJobs {
+    fn start(&self) -> (u64, Arc<AtomicBool>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let canceled = Arc::new(AtomicBool::new(false));
+        self.running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, canceled.clone());
+        (id, canceled)
+    }
+
+    fn finish(&self, id: u64) {
+        self.running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&id);
+    }
+}
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Intervalo mínimo entre eventos `transfer-progress`.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+// ==========================================
+// MODELOS
+// ==========================================
+
+/// Qué hacer si en el destino ya hay un elemento con el mismo nombre.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Añade un sufijo ` (2)`, ` (3)`... al nuevo.
+    #[default]
+    KeepBoth,
+    /// Sustituye al existente una vez copiado el nuevo. El existente va a
+    /// la papelera.
+    Replace,
+    Skip,
+    /// No toca el elemento y lo marca como `conflict` en el informe para que
+    /// la interfaz pregunte y repita la operación con la política elegida.
+    Ask,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferMode {
+    Copy,
+    Move,
+}
+
+/// Elementos a copiar o mover a `dest_dir`.
+pub(crate) struct Transfer {
+    pub sources: Vec<PathBuf>,
+    pub dest_dir: PathBuf,
+    pub mode: TransferMode,
+    pub conflict: ConflictPolicy,
+}
+
+#[derive(Clone, Serialize)]
+struct TransferProgress {
+    job_id: u64,
+    bytes_done: u64,
+    bytes_total: u64,
+    items_done: usize,
+    items_total: usize,
+    current: String,
+}
+
+enum TransferError {
+    Canceled,
+    Failed(ArchiError),
+}
+
+impl<T: Into<ArchiError>> From<T> for TransferError {
+    fn from(err: T) -> Self {
+        TransferError::Failed(err.into())
+    }
+}
+
+// ==========================================
+// PROGRESO
+// ==========================================
+
+struct Job<'a> {
+    app: &'a AppHandle,
+    id: u64,
+    canceled: &'a AtomicBool,
+    bytes_done: u64,
+    bytes_total: u64,
+    items_done: usize,
+    items_total: usize,
+    last_emit: Instant,
+}
+
+impl Job<'_> {
+    fn check_canceled(&self) -> Result<(), TransferError> {
+        if self.canceled.load(Ordering::Relaxed) {
+            Err(TransferError::Canceled)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn progress(&mut self, current: &Path, force: bool) {
+        if !force && self.last_emit.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        self.last_emit = Instant::now();
+        let _ = self.app.emit(
+            "transfer-progress",
+            TransferProgress {
+                job_id: self.id,
+                bytes_done: self.bytes_done,
+                bytes_total: self.bytes_total,
+                items_done: self.items_done,
+                items_total: self.items_total,
+                current: current.to_string_lossy().to_string(),
+            },
+        );
+    }
+}
+
+// ==========================================
+// MOTOR DE TRANSFERENCIAS
+// ==========================================
+
+/// Ejecuta `transfer` elemento a elemento, emitiendo `transfer-progress`.
+/// Bloquea hasta terminar o cancelarse: llamar desde `spawn_blocking`.
+pub(crate) fn run(app: &AppHandle, jobs: &TransferJobs, transfer: Transfer) -> TransferReport {
+    let (id, canceled) = jobs.start();
+    let mut job = Job {
+        app,
+        id,
+        canceled: &canceled,
+        bytes_done: 0,
+        bytes_total: transfer.sources.iter().map(|s| tree_size(s)).sum(),
+        items_done: 0,
+        items_total: transfer.sources.len(),
+        last_emit: Instant::now(),
+    };
+    job.progress(&transfer.dest_dir, true);
+
+    let mut items = Vec::new();
+    for src in &transfer.sources {
+        let item = transfer_item(&mut job, src, &transfer);
+        if let Some(error) = &item.error {
+            eprintln!("Error transfiriendo {:?}: {}", src, error);
+        }
+        items.push(item);
+        job.items_done += 1;
+        job.progress(src, true);
+    }
+
+    jobs.finish(id);
+    TransferReport {
+        job_id: id,
+        items,
+        bytes_transferred: job.bytes_done,
+        canceled: canceled.load(Ordering::Relaxed),
+    }
+}
+
+/// Copia o mueve `src` exactamente a `dest`, con progreso como una
+/// transferencia de un elemento. Lo usan deshacer y rehacer.
+pub(crate) fn transfer_path(
+    app: &AppHandle,
+    src: &Path,
+    dest: &Path,
+    mode: TransferMode,
+) -> Result<(), ArchiError> {
+    if fs::symlink_metadata(dest).is_ok() {
+        return Err(ArchiError::AlreadyExists(dest.to_path_buf()));
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| ArchiError::io(parent, e))?;
+    }
+
+    let jobs = app.state::<TransferJobs>();
+    let (id, canceled) = jobs.start();
+    let mut job = Job {
+        app,
+        id,
+        canceled: &canceled,
+        bytes_done: 0,
+        bytes_total: tree_size(src),
+        items_done: 0,
+        items_total: 1,
+        last_emit: Instant::now(),
+    };
+    let result = execute(&mut job, src, dest, mode, false);
+    job.items_done = 1;
+    job.progress(src, true);
+    jobs.finish(id);
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(TransferError::Canceled) => Err(ArchiError::Other("Operación cancelada".to_string())),
+        Err(TransferError::Failed(error)) => Err(error),
+    }
+}
+
+fn transfer_item(job: &mut Job, src: &Path, transfer: &Transfer) -> TransferItem {
+    let result = |status, destination: Option<&Path>, error| TransferItem {
+        source: src.to_string_lossy().to_string(),
+        destination: destination.map(|d| d.to_string_lossy().to_string()),
+        status,
+        error,
+        replaced: false,
+    };
+
+    if job.check_canceled().is_err() {
+        return result(TransferStatus::Canceled, None, None);
+    }
+    if !src.is_absolute() || fs::symlink_metadata(src).is_err() {
+        let error = ArchiError::NotFound(src.to_path_buf());
+        return result(TransferStatus::Failed, None, Some(error));
+    }
+    let dest_dir = &transfer.dest_dir;
+    // Mover a la misma carpeta o reemplazar un elemento por sí mismo no
+    // cambia nada; copiar en ella conservando ambos crea "nombre (2)"
+    let same_dir = src.parent() == Some(dest_dir.as_path());
+    if same_dir
+        && (transfer.mode == TransferMode::Move || transfer.conflict == ConflictPolicy::Replace)
+    {
+        return result(TransferStatus::Skipped, Some(src), None);
+    }
+    if dest_dir.starts_with(src) {
+        let error = ArchiError::Other(format!(
+            "No se puede copiar \"{}\" dentro de sí misma",
+            src.display()
+        ));
+        return result(TransferStatus::Failed, None, Some(error));
+    }
+    let Some(file_name) = src.file_name().and_then(|n| n.to_str()) else {
+        let error = ArchiError::NotFound(src.to_path_buf());
+        return result(TransferStatus::Failed, None, Some(error));
+    };
+
+    let target = dest_dir.join(file_name);
+    let (dest, replace) = if fs::symlink_metadata(&target).is_err() {
+        (target, false)
+    } else {
+        match transfer.conflict {
+            ConflictPolicy::KeepBoth => {
+                (unique_dest_path(dest_dir, file_name, src.is_dir()), false)
+            }
+            ConflictPolicy::Replace => (target, true),
+            ConflictPolicy::Skip => return result(TransferStatus::Skipped, Some(&target), None),
+            ConflictPolicy::Ask => return result(TransferStatus::Conflict, Some(&target), None),
+        }
+    };
+
+    match execute(job, src, &dest, transfer.mode, replace) {
+        Ok(()) => TransferItem {
+            replaced: replace,
+            ..result(TransferStatus::Done, Some(&dest), None)
+        },
+        Err(TransferError::Canceled) => result(TransferStatus::Canceled, None, None),
+        Err(TransferError::Failed(error)) => {
+            // Si el destino quedó completo, el fallo fue al borrar el original
+            let destination = dest.exists().then_some(dest.as_path());
+            result(TransferStatus::Failed, destination, Some(error))
+        }
+    }
+}
+
+/// Copia o mueve `src` a `dest`. Con `replace` el nuevo se prepara junto al
+/// existente y solo lo sustituye al final, así un fallo no pierde ninguno;
+/// el existente se envía a la papelera para poder deshacer el reemplazo.
+fn execute(
+    job: &mut Job,
+    src: &Path,
+    dest: &Path,
+    mode: TransferMode,
+    replace: bool,
+) -> Result<(), TransferError> {
+    let staging = if replace {
+        staging_path(dest)
+    } else {
+        dest.to_path_buf()
+    };
+
+    // Dentro del mismo disco mover es un renombrado
+    let renamed = mode == TransferMode::Move && fs::rename(src, &staging).is_ok();
+    if renamed {
+        job.bytes_done += tree_size(&staging);
+    } else if let Err(e) = copy_tree(job, src, &staging) {
+        let _ = remove_path(&staging);
+        return Err(e);
+    }
+
+    if replace {
+        let swapped = trash_bin::delete(&[dest.to_path_buf()])
+            .and_then(|_| fs::rename(&staging, dest).map_err(|e| ArchiError::io(dest, e)));
+        if let Err(e) = swapped {
+            if renamed {
+                let _ = fs::rename(&staging, src);
+            } else {
+                let _ = remove_path(&staging);
+            }
+            return Err(e.into());
+        }
+    }
+
+    if mode == TransferMode::Move && !renamed {
+        remove_path(src).map_err(|e| {
+            ArchiError::Other(format!(
+                "Se copió pero no se pudo borrar el original: {}",
+                e
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+fn copy_tree(job: &mut Job, src: &Path, dest: &Path) -> Result<(), TransferError> {
+    if !src.is_dir() {
+        return copy_file(job, src, dest);
+    }
+
+    for entry in WalkDir::new(src).follow_links(false) {
+        let entry = entry.map_err(|e| ArchiError::Other(e.to_string()))?;
+        let rel = entry
+            .path()
+            .strip_prefix(src)
+            .map_err(|e| ArchiError::Other(e.to_string()))?;
+        let dest_path = dest.join(rel);
+
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&dest_path).map_err(|e| ArchiError::io(&dest_path, e))?;
+        } else {
+            copy_file(job, entry.path(), &dest_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copia por bloques para poder informar del progreso y cancelar a mitad.
+fn copy_file(job: &mut Job, src: &Path, dest: &Path) -> Result<(), TransferError> {
+    job.check_canceled()?;
+
+    let mut input = File::open(src).map_err(|e| ArchiError::io(src, e))?;
+    let metadata = input.metadata().map_err(|e| ArchiError::io(src, e))?;
+    let mut output = File::create(dest).map_err(|e| ArchiError::io(dest, e))?;
+
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    loop {
+        job.check_canceled()?;
+        let read = input
+            .read(&mut buffer)
+            .map_err(|e| ArchiError::io(src, e))?;
+        if read == 0 {
+            break;
+        }
+        output
+            .write_all(&buffer[..read])
+            .map_err(|e| ArchiError::io(dest, e))?;
+        job.bytes_done += read as u64;
+        job.progress(src, false);
+    }
+
+    // Conservar permisos y fecha de modificación, como al arrastrar en Finder
+    let _ = output.set_permissions(metadata.permissions());
+    if let Ok(modified) = metadata.modified() {
+        let _ = output.set_modified(modified);
+    }
+    Ok(())
+}
+
+// ==========================================
+// FUNCIONES AUXILIARES
+// ==========================================
+
+/// Bytes de un archivo o de todo lo que contiene una carpeta.
+fn tree_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .flatten()
+        .filter(|e| !e.file_type().is_dir())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+pub(crate) fn remove_path(path: &Path) -> std::io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Nombre oculto junto a `dest` donde se prepara un reemplazo.
+fn staging_path(dest: &Path) -> PathBuf {
+    let name = dest.file_name().unwrap_or_default().to_string_lossy();
+    dest.with_file_name(format!(".{}.archi-{}", name, std::process::id()))
+}
+
+/// `nombre (2).ext`, `nombre (3).ext`... el primero libre en `dest_dir`.
+pub(crate) fn unique_dest_path(dest_dir: &Path, base_name: &str, is_dir: bool) -> PathBuf {
+    let initial = dest_dir.join(base_name);
+    if !initial.exists() {
+        return initial;
+    }
+
+    let (stem, ext) = if !is_dir {
+        match base_name.rsplit_once('.') {
+            Some((s, e)) if !s.is_empty() && !e.is_empty() => (s.to_string(), Some(e.to_string())),
+            _ => (base_name.to_string(), None),
+        }
+    } else {
+        (base_name.to_string(), None)
+    };
+
+    for n in 2..10_000 {
+        let candidate_name = match &ext {
+            Some(e) => format!("{} ({}).{}", stem, n, e),
+            None => format!("{} ({})", stem, n),
+        };
+        let candidate = dest_dir.join(candidate_name);
+        if !candidate.exists() {
+            return candidate;
+        }
+    }
+
+    dest_dir.join(base_name)
+}
+
+// ==========================================
+// COMANDOS
+// ==========================================
+
+/// Lanza `transfer` en segundo plano y espera su informe sin bloquear la
+/// interfaz.
+pub(crate) async fn spawn(
+    app: AppHandle,
+    transfer: Transfer,
+) -> Result<TransferReport, ArchiError> {
+    let mode = transfer.mode;
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let jobs = app.state::<TransferJobs>();
+        run(&app, &jobs, transfer)
+    })
+    .await
+    .map_err(|e| ArchiError::Other(e.to_string()))?;
+
+    journal::record_transfer(mode, &report);
+    Ok(report)
+}
+
+/// Importa a `section` (`Biblioteca` o `Recursos`) de `folder` los elementos
+/// soltados sobre la ventana. Sin `copy_mode` se mueven.
+pub(crate) async fn import_dropped(
+    app: AppHandle,
+    folder: &str,
+    section: &str,
+    paths: Vec<String>,
+    copy_mode: bool,
+    conflict: Option<ConflictPolicy>,
+) -> Result<TransferReport, ArchiError> {
+    let dest_dir = resolve_path(folder, section)?;
+    if !dest_dir.is_dir() {
+        return Err(ArchiError::NotFound(dest_dir));
+    }
+
+    let mode = if copy_mode {
+        TransferMode::Copy
+    } else {
+        TransferMode::Move
+    };
+    let transfer = Transfer {
+        sources: paths.into_iter().map(PathBuf::from).collect(),
+        dest_dir,
+        mode,
+        conflict: conflict.unwrap_or_default(),
+    };
+    spawn(app, transfer).await
+}
+
+/// Mueve o copia `items` (`Cliente/Trabajo/archivo` dentro de `section`) a
+/// `dest_folder` dentro de `dest_section`. Sirve entre clientes y entre
+/// `Biblioteca` y `Recursos`.
+async fn transfer_items(
+    app: AppHandle,
+    mode: TransferMode,
+    section: &str,
+    items: Vec<String>,
+    dest_section: &str,
+    dest_folder: &str,
+    conflict: Option<ConflictPolicy>,
+) -> Result<TransferReport, ArchiError> {
+    let dest_dir = resolve_path(dest_folder, dest_section)?;
+    if !dest_dir.is_dir() {
+        return Err(ArchiError::NotFound(dest_dir));
+    }
+
+    let mut sources = Vec::new();
+    for item in &items {
+        // Solo el cliente señalaría la sección entera
+        if !item.contains('/') {
+            return Err(PathError::Empty.into());
+        }
+        sources.push(resolve_path(item, section)?);
+    }
+
+    let transfer = Transfer {
+        sources,
+        dest_dir,
+        mode,
+        conflict: conflict.unwrap_or_default(),
+    };
+    let report = spawn(app, transfer).await?;
+    carry_layout(&report, mode);
+    Ok(report)
+}
+
+/// Lleva la posición en la cuadrícula de cada elemento transferido a la
+/// carpeta de destino (con su nombre final). Al mover se quita del origen.
+fn carry_layout(report: &TransferReport, mode: TransferMode) {
+    for item in &report.items {
+        let Some(destination) = &item.destination else {
+            continue;
+        };
+        if item.status != TransferStatus::Done {
+            continue;
+        }
+        let (src, dest) = (Path::new(&item.source), Path::new(destination));
+        let (Some(src_dir), Some(src_name)) = (src.parent(), src.file_name()) else {
+            continue;
+        };
+        let (Some(dest_dir), Some(dest_name)) = (dest.parent(), dest.file_name()) else {
+            continue;
+        };
+        let (src_name, dest_name) = (src_name.to_string_lossy(), dest_name.to_string_lossy());
+
+        let position = metadata_store::read(src_dir)
+            .layout
+            .and_then(|layout| layout.get(src_name.as_ref()).cloned());
+        let Some(position) = position else {
+            continue;
+        };
+
+        let _ = metadata_store::update(dest_dir, |meta| {
+            meta.layout
+                .get_or_insert_with(Default::default)
+                .insert(dest_name.to_string(), position);
+        });
+        if mode == TransferMode::Move {
+            let _ = metadata_store::update(src_dir, |meta| {
+                if let Some(layout) = meta.layout.as_mut() {
+                    layout.remove(src_name.as_ref());
+                }
+            });
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn move_items(
+    app: AppHandle,
+    section: String,
+    items: Vec<String>,
+    dest_section: String,
+    dest_folder: String,
+    conflict: Option<ConflictPolicy>,
+) -> Result<TransferReport, ArchiError> {
+    transfer_items(
+        app,
+        TransferMode::Move,
+        &section,
+        items,
+        &dest_section,
+        &dest_folder,
+        conflict,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn copy_items(
+    app: AppHandle,
+    section: String,
+    items: Vec<String>,
+    dest_section: String,
+    dest_folder: String,
+    conflict: Option<ConflictPolicy>,
+) -> Result<TransferReport, ArchiError> {
+    transfer_items(
+        app,
+        TransferMode::Copy,
+        &section,
+        items,
+        &dest_section,
+        &dest_folder,
+        conflict,
+    )
+    .await
+}
+
+/// Cancela la transferencia `job_id` (la de los eventos `transfer-progress`),
+/// o todas si no se indica. Lo ya transferido se conserva.
+#[tauri::command]
+pub fn cancel_transfer(state: State<TransferJobs>, job_id: Option<u64>) -> Result<(), ArchiError> {
+    let running = state.running.lock().map_err(|e| e.to_string())?;
+    for (id, canceled) in running.iter() {
+        if job_id.is_none_or(|job_id| job_id == *id) {
+            canceled.store(true, Ordering::Relaxed);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,211 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use trash::TrashItem;
+
+use crate::error::ArchiError;
+use crate::models::{TrashedClient, TrashedItem};
+use crate::paths::get_user_data_dir;
+use crate::validation::client_path;
+
+// ==========================================
+// ACCESO A LA PAPELERA
+// ==========================================
+
+// Listar y recuperar solo está disponible en Windows y en la papelera
+// freedesktop (Linux); en macOS estas funciones devuelven un error.
+
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+mod platform {
+    use trash::os_limited;
+    use trash::TrashItem;
+
+    pub fn list() -> Result<Vec<TrashItem>, trash::Error> {
+        os_limited::list()
+    }
+
+    pub fn restore(items: Vec<TrashItem>) -> Result<(), trash::Error> {
+        os_limited::restore_all(items)
+    }
+
+    pub fn purge(items: Vec<TrashItem>) -> Result<(), trash::Error> {
+        os_limited::purge_all(items)
+    }
+
+    pub fn is_dir(item: &TrashItem) -> bool {
+        os_limited::metadata(item).is_ok_and(|meta| meta.size.size().is_none())
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+mod platform {
+    use trash::TrashItem;
+
+    fn unsupported() -> trash::Error {
+        trash::Error::Unknown {
+            description: "Este sistema no permite abrir la papelera desde ARCHI".to_string(),
+        }
+    }
+
+    pub fn list() -> Result<Vec<TrashItem>, trash::Error> {
+        Err(unsupported())
+    }
+
+    pub fn restore(_items: Vec<TrashItem>) -> Result<(), trash::Error> {
+        Err(unsupported())
+    }
+
+    pub fn purge(_items: Vec<TrashItem>) -> Result<(), trash::Error> {
+        Err(unsupported())
+    }
+
+    pub fn is_dir(_item: &TrashItem) -> bool {
+        false
+    }
+}
+
+/// Envía `paths` a la papelera del sistema.
+pub(crate) fn delete(paths: &[PathBuf]) -> Result<(), ArchiError> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    trash::delete_all(paths)?;
+    Ok(())
+}
+
+/// Devuelve a su sitio lo último que se envió a la papelera desde cada una
+/// de `paths`.
+pub(crate) fn restore(paths: &[PathBuf]) -> Result<(), ArchiError> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    let mut latest: HashMap<&Path, TrashItem> = HashMap::new();
+    for item in platform::list()? {
+        let original = item.original_path();
+        let Some(path) = paths.iter().find(|p| **p == original) else {
+            continue;
+        };
+        match latest.get(path.as_path()) {
+            Some(newer) if newer.time_deleted >= item.time_deleted => {}
+            _ => {
+                latest.insert(path, item);
+            }
+        }
+    }
+
+    if let Some(missing) = paths.iter().find(|p| !latest.contains_key(p.as_path())) {
+        return Err(ArchiError::Other(format!(
+            "\"{}\" ya no está en la papelera",
+            missing.display()
+        )));
+    }
+    platform::restore(latest.into_values().collect())?;
+    Ok(())
+}
+
+// ==========================================
+// FUNCIONES AUXILIARES
+// ==========================================
+
+/// Cliente y sección de un elemento de la papelera, si su ruta original está
+/// dentro de un cliente de la biblioteca activa.
+fn locate(root: &Path, item: &TrashItem) -> Option<(String, Option<String>)> {
+    let original = item.original_path();
+    let relative = original.strip_prefix(root).ok()?;
+    let mut parts = relative.components().map(|c| match c {
+        Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+        _ => None,
+    });
+
+    let client = parts.next()??;
+    // Solo nombres de cliente válidos: fuera la caché, ocultos, reservados...
+    client_path(&client).ok()?;
+    // Sin más partes es el propio cliente, que debe ser una carpeta (no
+    // config.json u otros archivos sueltos junto a los clientes)
+    let section = parts.next().flatten();
+    if section.is_none() && !platform::is_dir(item) {
+        return None;
+    }
+    Some((client, section))
+}
+
+/// Elementos de la papelera con uno de los `ids` pedidos que salieron de la
+/// biblioteca activa; los demás se ignoran.
+fn library_items(ids: &[String]) -> Result<Vec<TrashItem>, ArchiError> {
+    let root = get_user_data_dir();
+    Ok(platform::list()?
+        .into_iter()
+        .filter(|item| ids.contains(&item.id.to_string_lossy().to_string()))
+        .filter(|item| locate(&root, item).is_some())
+        .collect())
+}
+
+// ==========================================
+// COMANDOS
+// ==========================================
+
+/// Elementos de la papelera cuya ruta original está en la biblioteca activa,
+/// agrupados por cliente y del más reciente al más antiguo.
+#[tauri::command]
+pub fn list_trashed_items() -> Result<Vec<TrashedClient>, ArchiError> {
+    let root = get_user_data_dir();
+    let mut groups: HashMap<String, Vec<TrashedItem>> = HashMap::new();
+
+    for item in platform::list()? {
+        let Some((client, section)) = locate(&root, &item) else {
+            continue;
+        };
+        groups.entry(client).or_default().push(TrashedItem {
+            id: item.id.to_string_lossy().to_string(),
+            name: item.name.to_string_lossy().to_string(),
+            original_path: item.original_path().to_string_lossy().to_string(),
+            section,
+            deleted_at: item.time_deleted,
+        });
+    }
+
+    let mut clients: Vec<TrashedClient> = groups
+        .into_iter()
+        .map(|(client, mut items)| {
+            items.sort_by_key(|item| Reverse(item.deleted_at));
+            TrashedClient { client, items }
+        })
+        .collect();
+    clients.sort_by_key(|c| c.client.to_lowercase());
+    Ok(clients)
+}
+
+/// Devuelve a su ubicación original los elementos `ids` de la papelera.
+#[tauri::command]
+pub fn restore_trashed_items(ids: Vec<String>) -> Result<usize, ArchiError> {
+    let items = library_items(&ids)?;
+    let count = items.len();
+    platform::restore(items)?;
+    Ok(count)
+}
+
+/// Borra definitivamente los elementos `ids` de la papelera.
+#[tauri::command]
+pub fn purge_trashed_items(ids: Vec<String>) -> Result<usize, ArchiError> {
+    let items = library_items(&ids)?;
+    let count = items.len();
+    platform::purge(items)?;
+    Ok(count)
+}
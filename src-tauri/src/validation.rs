@@ -0,0 +1,305 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::paths::get_user_data_dir;
+
+// ==========================================
+// ERRORES
+// ==========================================
+
+/// Motivo por el que se rechaza un nombre o una ruta recibidos del frontend.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathError {
+    Empty,
+    /// `.` o `..`: saldría de la carpeta actual.
+    Traversal(String),
+    /// Contiene `/` o `\`.
+    Separator(String),
+    /// Nombre reservado por el sistema (`CON`, `NUL`...) o por ARCHI.
+    Reserved(String),
+    InvalidChar(String, char),
+    /// Empieza por `.`: quedaría oculto en la interfaz.
+    Hidden(String),
+    TooLong(String),
+    /// Ruta absoluta fuera de la carpeta de ARCHI.
+    OutsideLibrary(String),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::Empty => write!(f, "El nombre no puede estar vacío"),
+            PathError::Traversal(name) => write!(f, "Nombre no permitido: \"{}\"", name),
+            PathError::Separator(name) => {
+                write!(f, "El nombre no puede contener / ni \\: \"{}\"", name)
+            }
+            PathError::Reserved(name) => write!(f, "\"{}\" es un nombre reservado", name),
+            PathError::InvalidChar(name, c) => {
+                write!(
+                    f,
+                    "El nombre \"{}\" contiene un carácter no válido: {:?}",
+                    name, c
+                )
+            }
+            PathError::Hidden(name) => {
+                write!(f, "El nombre no puede empezar por un punto: \"{}\"", name)
+            }
+            PathError::TooLong(name) => write!(f, "El nombre es demasiado largo: \"{}\"", name),
+            PathError::OutsideLibrary(path) => {
+                write!(f, "Acceso denegado fuera de la biblioteca: {}", path)
+            }
+        }
+    }
+}
+
+impl From<PathError> for String {
+    fn from(err: PathError) -> Self {
+        err.to_string()
+    }
+}
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+/// Caracteres que Windows no admite en nombres de archivo.
+const INVALID_CHARS: &[char] = &['<', '>', ':', '"', '|', '?', '*'];
+
+/// Nombres de dispositivo de Windows, con o sin extensión.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Carpetas de ARCHI junto a los clientes que no pueden usarse como cliente.
+const RESERVED_CLIENTS: &[&str] = &["cache"];
+
+/// Límite de la mayoría de sistemas de archivos (bytes).
+const MAX_NAME_BYTES: usize = 255;
+
+// ==========================================
+// NOMBRES
+// ==========================================
+
+/// Comprobación mínima para nombres que ya existen en disco: un solo
+/// componente que no suba de nivel.
+fn check_segment(name: &str) -> Result<(), PathError> {
+    if name.is_empty() {
+        return Err(PathError::Empty);
+    }
+    if name == "." || name == ".." {
+        return Err(PathError::Traversal(name.to_string()));
+    }
+    if name.contains(['/', '\\']) {
+        return Err(PathError::Separator(name.to_string()));
+    }
+    if name.contains('\0') {
+        return Err(PathError::InvalidChar(name.to_string(), '\0'));
+    }
+    Ok(())
+}
+
+/// Valida un nombre nuevo (cliente, carpeta o recurso) y lo devuelve
+/// normalizado, sin espacios al principio ni al final.
+pub(crate) fn validate_name(name: &str) -> Result<String, PathError> {
+    let name = name.trim();
+    check_segment(name)?;
+
+    if name.starts_with('.') {
+        return Err(PathError::Hidden(name.to_string()));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| c.is_control() || INVALID_CHARS.contains(c))
+    {
+        return Err(PathError::InvalidChar(name.to_string(), c));
+    }
+    // Windows elimina el punto final en silencio
+    if name.ends_with('.') {
+        return Err(PathError::InvalidChar(name.to_string(), '.'));
+    }
+    if name.len() > MAX_NAME_BYTES {
+        return Err(PathError::TooLong(name.to_string()));
+    }
+
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        return Err(PathError::Reserved(name.to_string()));
+    }
+    Ok(name.to_string())
+}
+
+// ==========================================
+// RUTAS DE LA BIBLIOTECA
+// ==========================================
+
+/// Carpeta de un cliente existente o por crear.
+pub(crate) fn client_path(name: &str) -> Result<PathBuf, PathError> {
+    check_segment(name)?;
+    if name.starts_with('.')
+        || RESERVED_CLIENTS
+            .iter()
+            .any(|r| r.eq_ignore_ascii_case(name))
+    {
+        return Err(PathError::Reserved(name.to_string()));
+    }
+    Ok(get_user_data_dir().join(name))
+}
+
+/// Convierte una carpeta del frontend (`Cliente/Trabajo/Sub`) en su ruta dentro
+/// de `subfolder` (`Biblioteca`, `Recursos`...) del cliente.
+pub(crate) fn resolve_path(folder: &str, subfolder: &str) -> Result<PathBuf, PathError> {
+    let mut parts = folder.split('/');
+    let client = parts.next().unwrap_or_default();
+
+    check_segment(subfolder)?;
+    let mut path = client_path(client)?.join(subfolder);
+    for part in parts {
+        check_segment(part)?;
+        path.push(part);
+    }
+    Ok(path)
+}
+
+/// Elemento `name` dentro de una carpeta ya resuelta.
+pub(crate) fn child_path(base: &Path, name: &str) -> Result<PathBuf, PathError> {
+    check_segment(name)?;
+    Ok(base.join(name))
+}
+
+/// Ruta absoluta recibida del frontend, resuelta (enlaces incluidos) y
+/// comprobada dentro de la carpeta de ARCHI.
+pub(crate) fn library_path(path: &str) -> Result<PathBuf, PathError> {
+    let outside = || PathError::OutsideLibrary(path.to_string());
+
+    let root = get_user_data_dir().canonicalize().map_err(|_| outside())?;
+    let resolved = Path::new(path).canonicalize().map_err(|_| outside())?;
+    if !resolved.starts_with(&root) {
+        return Err(outside());
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::OnceLock;
+
+    use super::*;
+    use crate::paths::set_active_library;
+
+    /// Biblioteca temporal compartida por todas las pruebas: la biblioteca
+    /// activa es global.
+    fn library() -> &'static Path {
+        static ROOT: OnceLock<PathBuf> = OnceLock::new();
+        ROOT.get_or_init(|| {
+            let root =
+                std::env::temp_dir().join(format!("archi-validation-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(root.join("Cliente/Biblioteca")).unwrap();
+            let root = root.canonicalize().unwrap();
+            set_active_library(root.clone());
+            root
+        })
+    }
+
+    #[test]
+    fn rejects_traversal() {
+        library();
+        assert_eq!(validate_name(".."), Err(PathError::Traversal("..".into())));
+        assert_eq!(validate_name("."), Err(PathError::Traversal(".".into())));
+        assert!(client_path("..").is_err());
+        assert!(resolve_path("Cliente/../..", "Biblioteca").is_err());
+        assert!(child_path(Path::new("/tmp"), "..").is_err());
+    }
+
+    #[test]
+    fn rejects_separators() {
+        for name in ["a/b", "a\\b", "/etc", "..\\Windows"] {
+            assert_eq!(validate_name(name), Err(PathError::Separator(name.into())));
+            assert!(client_path(name).is_err());
+        }
+        assert!(child_path(Path::new("/tmp"), "sub/../../x").is_err());
+    }
+
+    #[test]
+    fn rejects_nul_and_control_chars() {
+        assert_eq!(
+            validate_name("a\0b"),
+            Err(PathError::InvalidChar("a\0b".into(), '\0'))
+        );
+        assert!(client_path("a\0b").is_err());
+        assert!(validate_name("a\nb").is_err());
+        assert!(validate_name("a:b").is_err());
+    }
+
+    #[test]
+    fn rejects_windows_reserved_names() {
+        for name in ["CON", "nul", "Com1", "LPT9", "aux.txt", "con .txt"] {
+            assert_eq!(validate_name(name), Err(PathError::Reserved(name.into())));
+        }
+        assert!(validate_name("CONTRATO").is_ok());
+        assert!(validate_name("com10").is_ok());
+    }
+
+    #[test]
+    fn trailing_dots_and_spaces() {
+        assert!(validate_name("Logo.").is_err());
+        assert!(validate_name("Logo. ").is_err());
+        assert_eq!(validate_name("  Logo final  ").unwrap(), "Logo final");
+        assert_eq!(validate_name("   "), Err(PathError::Empty));
+    }
+
+    #[test]
+    fn rejects_hidden_and_long_names() {
+        assert_eq!(validate_name(".git"), Err(PathError::Hidden(".git".into())));
+        assert!(validate_name(&"a".repeat(MAX_NAME_BYTES + 1)).is_err());
+        assert!(validate_name(&"a".repeat(MAX_NAME_BYTES)).is_ok());
+    }
+
+    #[test]
+    fn reserved_client_names() {
+        let root = library();
+        assert_eq!(
+            client_path("cache"),
+            Err(PathError::Reserved("cache".into()))
+        );
+        assert!(client_path("Cache").is_err());
+        assert!(client_path(".oculto").is_err());
+        assert!(resolve_path("cache/x", "Biblioteca").is_err());
+        assert_eq!(client_path("Cliente").unwrap(), root.join("Cliente"));
+        assert_eq!(
+            resolve_path("Cliente/Trabajo", "Biblioteca").unwrap(),
+            root.join("Cliente/Biblioteca/Trabajo")
+        );
+    }
+
+    #[test]
+    fn library_path_stays_inside_root() {
+        let root = library();
+        let inside = root.join("Cliente/Biblioteca");
+        assert_eq!(library_path(&inside.to_string_lossy()).unwrap(), inside);
+
+        let escaped = root.join("Cliente/Biblioteca/../../..");
+        assert!(matches!(
+            library_path(&escaped.to_string_lossy()),
+            Err(PathError::OutsideLibrary(_))
+        ));
+        assert!(library_path(&root.join("no-existe").to_string_lossy()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn library_path_rejects_symlink_outside_root() {
+        let root = library();
+        let outside = root.parent().unwrap();
+        let link = root.join("Cliente/Biblioteca/enlace");
+        let _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink(outside, &link).unwrap();
+
+        assert!(matches!(
+            library_path(&link.to_string_lossy()),
+            Err(PathError::OutsideLibrary(_))
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::error::ArchiError;
+use crate::models::{FsChange, FsChangeKind};
+use crate::validation::client_path;
+
+// ==========================================
+// STATE
+// ==========================================
+
+/// Vigilancia activa sobre las carpetas del cliente abierto en la interfaz.
+#[derive(Default)]
+pub struct FsWatcher {
+    active: Mutex<Option<ActiveWatch>>,
+}
+
+impl FsWatcher {
+    /// Detiene la vigilancia actual, si la hay.
+    pub(crate) fn stop(&self) {
+        *self.active.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+struct ActiveWatch {
+    /// Carpeta del cliente; con varias bibliotecas el nombre no basta.
+    client_dir: PathBuf,
+    // Al soltarlo se detiene la vigilancia
+    _debouncer: Debouncer<RecommendedWatcher, RecommendedCache>,
+}
+
+// ==========================================
+// CONSTANTES
+// ==========================================
+
+/// Carpetas de cada cliente que se vigilan.
+const SECTIONS: &[&str] = &["Biblioteca", "Recursos"];
+
+/// Espera para agrupar ráfagas de eventos (guardados por partes, copias grandes...).
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+
+// ==========================================
+// COMMANDS
+// ==========================================
+
+/// Vigila la Biblioteca y los Recursos de `name` y emite `fs-changed` con los
+/// cambios agrupados. Sustituye a la vigilancia anterior; `None` la detiene.
+#[tauri::command]
+pub fn watch_client(
+    app: AppHandle,
+    state: State<FsWatcher>,
+    name: Option<String>,
+) -> Result<(), ArchiError> {
+    let mut active = state.active.lock().map_err(|e| e.to_string())?;
+
+    let client_dir = name.as_deref().map(client_path).transpose()?;
+    if active.as_ref().map(|w| &w.client_dir) == client_dir.as_ref() {
+        return Ok(());
+    }
+    *active = None;
+
+    let Some(client_dir) = client_dir else {
+        return Ok(());
+    };
+    if !client_dir.is_dir() {
+        return Err(ArchiError::NotFound(client_dir));
+    }
+
+    let handler_dir = client_dir.clone();
+    let mut debouncer = new_debouncer(DEBOUNCE_DELAY, None, move |result: DebounceEventResult| {
+        if let Ok(events) = result {
+            let changes = collect_changes(&handler_dir, events);
+            if !changes.is_empty() {
+                let _ = app.emit("fs-changed", changes);
+            }
+        }
+    })
+    .map_err(|e| e.to_string())?;
+
+    for section in SECTIONS {
+        let dir = client_dir.join(section);
+        if dir.is_dir() {
+            debouncer
+                .watch(&dir, RecursiveMode::Recursive)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    *active = Some(ActiveWatch {
+        client_dir,
+        _debouncer: debouncer,
+    });
+    Ok(())
+}
+
+// ==========================================
+// TRADUCCIÓN DE EVENTOS
+// ==========================================
+
+fn collect_changes(
+    client_dir: &Path,
+    events: Vec<notify_debouncer_full::DebouncedEvent>,
+) -> Vec<FsChange> {
+    let mut changes: Vec<FsChange> = Vec::new();
+
+    for event in events {
+        let kind = match event.kind {
+            EventKind::Create(_) => FsChangeKind::Created,
+            EventKind::Remove(_) => FsChangeKind::Removed,
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                FsChangeKind::Renamed
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => FsChangeKind::Removed,
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => FsChangeKind::Created,
+            // FSEvents no distingue origen y destino: se deduce de si la ruta existe
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                if event.paths.iter().any(|p| p.exists()) {
+                    FsChangeKind::Created
+                } else {
+                    FsChangeKind::Removed
+                }
+            }
+            // Cambios de permisos o fechas de acceso no afectan a la interfaz
+            EventKind::Modify(ModifyKind::Metadata(_)) => continue,
+            EventKind::Modify(_) => FsChangeKind::Modified,
+            _ => continue,
+        };
+
+        if kind == FsChangeKind::Renamed {
+            let (from, to) = (&event.paths[0], &event.paths[1]);
+            match (locate(client_dir, from), locate(client_dir, to)) {
+                // Mismo destino visible: un solo cambio con ambas rutas
+                (Some(a), Some(b)) if a == b => push_change(&mut changes, kind, a, vec![from, to]),
+                (a, b) => {
+                    if let Some(a) = a {
+                        push_change(&mut changes, FsChangeKind::Removed, a, vec![from]);
+                    }
+                    if let Some(b) = b {
+                        push_change(&mut changes, FsChangeKind::Created, b, vec![to]);
+                    }
+                }
+            }
+            continue;
+        }
+
+        for path in &event.paths {
+            if let Some(location) = locate(client_dir, path) {
+                push_change(&mut changes, kind, location, vec![path]);
+            }
+        }
+    }
+    changes
+}
+
+fn push_change(
+    changes: &mut Vec<FsChange>,
+    kind: FsChangeKind,
+    (section, folder): (String, String),
+    paths: Vec<&PathBuf>,
+) {
+    let paths: Vec<String> = paths
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+
+    // El debouncer puede repetir el mismo cambio en una ráfaga
+    if changes.iter().any(|c| c.kind == kind && c.paths == paths) {
+        return;
+    }
+    changes.push(FsChange {
+        kind,
+        section,
+        folder,
+        paths,
+    });
+}
+
+/// Sección (`Biblioteca`/`Recursos`) y carpeta en formato del frontend
+/// (`Cliente/Trabajo/...`) que contiene `path`. `None` para rutas ocultas o
+/// fuera del cliente.
+fn locate(client_dir: &Path, path: &Path) -> Option<(String, String)> {
+    let relative = path.strip_prefix(client_dir).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+
+    // Sección + al menos el propio archivo; sin ocultos (.metadatos.json, temporales)
+    if parts.len() < 2 || parts.iter().any(|p| p.starts_with('.')) {
+        return None;
+    }
+    let section = parts[0].clone();
+    if !SECTIONS.contains(&section.as_str()) {
+        return None;
+    }
+
+    let client = client_dir.file_name()?.to_string_lossy().to_string();
+    let folder = std::iter::once(client)
+        .chain(parts[1..parts.len() - 1].iter().cloned())
+        .collect::<Vec<_>>()
+        .join("/");
+    Some((section, folder))
+}
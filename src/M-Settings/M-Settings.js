@@ -252,6 +252,9 @@ document.addEventListener('DOMContentLoaded', () => {
                     img.classList.remove('thumb-fit-contain');
                 }
             });
+
+            // Cada modo tiene su propia miniatura en caché (fill viene recortada)
+            if (window.utils) window.utils.refreshThumbnails();
         });
 
         const container = thumbToggle.closest('.system-preference-config');
@@ -335,20 +338,26 @@ document.addEventListener('DOMContentLoaded', () => {
                 }
 
                 if (totalSizeEl && typeof stats.total_size === 'number') {
-                    const size = stats.total_size;
-                    if (window.utils && typeof window.utils.formatSize === 'function') {
-                        totalSizeEl.textContent = window.utils.formatSize(size);
-                    } else {
+                    const formatBytes = (size) => {
+                        if (window.utils && typeof window.utils.formatSize === 'function') {
+                            return window.utils.formatSize(size);
+                        }
                         const k = 1024;
                         const sizes = ['B', 'KB', 'MB', 'GB'];
-                        if (!size || size === 0) {
-                            totalSizeEl.textContent = '0 B';
-                        } else {
-                            const i = Math.floor(Math.log(size) / Math.log(k));
-                            const value = parseFloat((size / Math.pow(k, i)).toFixed(1));
-                            totalSizeEl.textContent = `${value} ${sizes[i]}`;
-                        }
+                        if (!size || size === 0) return '0 B';
+                        const i = Math.floor(Math.log(size) / Math.log(k));
+                        const value = parseFloat((size / Math.pow(k, i)).toFixed(1));
+                        return `${value} ${sizes[i]}`;
+                    };
+
+                    let text = formatBytes(stats.total_size);
+                    if (stats.max_size > 0) {
+                        text += ` de ${formatBytes(stats.max_size)}`;
                     }
+                    totalSizeEl.textContent = text;
+                    totalSizeEl.title = stats.eviction_count > 0
+                        ? `${stats.eviction_count} miniaturas liberadas por el límite de tamaño`
+                        : '';
                 }
             } catch (error) {
                 if (itemsCountEl) itemsCountEl.textContent = '—';
@@ -392,7 +401,13 @@ document.addEventListener('DOMContentLoaded', () => {
                 }
 
                 try {
-                    const result = await window.__TAURI__.core.invoke('generate_missing_thumbnails');
+                    // La carpeta abierta en Biblioteca se genera primero
+                    const openGrid = ['grid-work', 'grid-library']
+                        .map(id => document.getElementById(id))
+                        .find(grid => grid && grid.style.display !== 'none' && grid.getAttribute('data-current-folder'));
+                    const priorityFolder = openGrid ? openGrid.getAttribute('data-current-folder') : null;
+
+                    const result = await window.__TAURI__.core.invoke('generate_missing_thumbnails', { priorityFolder });
                     
                     if (window.loader) {
                         window.loader.hide();
@@ -404,7 +419,7 @@ document.addEventListener('DOMContentLoaded', () => {
                     if (window.loader) {
                         window.loader.hide();
                     }
-                    showStatus(`Error: ${error}`, true);
+                    showStatus(`Error: ${window.utils.errorMessage(error)}`, true);
                 } finally {
                     if (unlisten) unlisten();
                 }
@@ -416,7 +431,7 @@ document.addEventListener('DOMContentLoaded', () => {
                 try {
                     await window.__TAURI__.core.invoke('open_cache_folder');
                 } catch (error) {
-                    showStatus(`Error: ${error}`, true);
+                    showStatus(`Error: ${window.utils.errorMessage(error)}`, true);
                 }
             });
         }
@@ -429,7 +444,7 @@ document.addEventListener('DOMContentLoaded', () => {
                     showStatus('Cache limpiado exitosamente');
                     updateCacheInfo();
                 } catch (error) {
-                    showStatus(`Error: ${error}`, true);
+                    showStatus(`Error: ${window.utils.errorMessage(error)}`, true);
                 }
             });
         }
@@ -438,6 +453,50 @@ document.addEventListener('DOMContentLoaded', () => {
     };
     setupCacheActions();
 
+    // --- Bibliotecas: una fila por biblioteca, clic para abrirla ---
+    const libraryList = document.getElementById('library-list');
+
+    const refreshLibraries = async () => {
+        if (!libraryList) return;
+        const result = await window.utils.apiCall('/api/libraries', null, 'GET');
+        if (result.status !== 'success') return;
+
+        libraryList.innerHTML = '';
+        result.libraries.forEach(library => {
+            const row = document.createElement('div');
+            row.className = 'system-preference-config';
+
+            const icon = document.createElement('span');
+            icon.className = 'material-symbols-rounded';
+            icon.style.color = library.active ? 'var(--accent-color)' : '#8b949e';
+            icon.textContent = library.active ? 'check_circle' : (library.available ? 'folder' : 'folder_off');
+
+            const info = document.createElement('div');
+            info.className = 'settings-info';
+            const label = document.createElement('span');
+            label.className = 'settings-label';
+            label.textContent = library.name;
+            const description = document.createElement('span');
+            description.className = 'settings-description';
+            description.textContent = library.available ? library.path : `${library.path} (no disponible)`;
+            info.append(label, description);
+
+            row.append(icon, info);
+            row.addEventListener('click', async () => {
+                if (library.active || !library.available) return;
+                const switched = await window.utils.apiCall('/api/switch_library', { path: library.path }, 'POST');
+                if (switched.status !== 'success') {
+                    window.utils.showError('No se pudo abrir la biblioteca', switched.error);
+                }
+            });
+            libraryList.appendChild(row);
+        });
+    };
+
+    if (window.__TAURI__ && window.__TAURI__.event) {
+        window.__TAURI__.event.listen('library-changed', refreshLibraries);
+    }
+
     // --- Lógica de navegación del menú lateral ---
     const menuItems = document.querySelectorAll('.settings-menu-item');
     const sections = document.querySelectorAll('.settings-section-content');
@@ -483,6 +542,7 @@ document.addEventListener('DOMContentLoaded', () => {
             updateSlidersFromCSS('primary', 'primary-color');
             updateSlidersFromCSS('accent', 'accent-color');
             if (typeof refreshCacheInfo === 'function') refreshCacheInfo();
+            refreshLibraries();
         }
     });
 
@@ -507,8 +567,8 @@ document.addEventListener('DOMContentLoaded', () => {
 
         const thumbToggle = document.getElementById('thumbnail-mode-toggle');
         if (thumbToggle) {
-            const mode = config.thumbnail_mode || 'fit';
-            thumbToggle.checked = (mode === 'fit');
+            const mode = String(config.thumbnail_mode || 'fit').toLowerCase();
+            thumbToggle.checked = (mode !== 'fill');
         }
     });
 });
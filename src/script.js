@@ -13,6 +13,13 @@ document.addEventListener('DOMContentLoaded', async () => {
     let dropCopyMode = false;
     let isAltPressed = false;
 
+    // Texto seguro para insertar en innerHTML (p. ej. nombres de archivo en modales)
+    const escapeHtml = (text) => String(text)
+        .replace(/&/g, '&amp;')
+        .replace(/</g, '&lt;')
+        .replace(/>/g, '&gt;')
+        .replace(/"/g, '&quot;');
+
     const isTextEditingElement = (el) => {
         if (!el) return false;
         const tag = el.tagName;
@@ -41,6 +48,24 @@ document.addEventListener('DOMContentLoaded', async () => {
                     return { status: 'success' };
                 }
 
+                // --- BIBLIOTECAS ---
+                if (url === '/api/libraries' && method === 'GET') {
+                    const libraries = await invoke('list_libraries');
+                    return { status: 'success', libraries };
+                }
+                if (url === '/api/libraries' && method === 'POST') {
+                    const library = await invoke('add_library', { path: body.path, name: body.name || null });
+                    return { status: 'success', library };
+                }
+                if (url === '/api/switch_library') {
+                    const library = await invoke('switch_library', { path: body.path });
+                    return { status: 'success', library };
+                }
+                if (url === '/api/remove_library') {
+                    await invoke('remove_library', { path: body.path });
+                    return { status: 'success' };
+                }
+
                 // --- RECURSOS ---
                 // Patrón: /api/files_resources/{folderName}
                 if (url.startsWith('/api/files_resources/')) {
@@ -174,21 +199,48 @@ document.addEventListener('DOMContentLoaded', async () => {
                 }
 
                 if (url === '/api/import_drop' && method === 'POST') {
-                    await invoke('import_dropped_items', { 
+                    const report = await invoke('import_dropped_items', { 
                         folder: body.folder, 
                         paths: body.paths,
-                        copyMode: body.copyMode 
+                        copyMode: body.copyMode,
+                        conflict: body.conflict || null
                     });
-                    return { status: 'success' };
+                    return { status: 'success', report };
                 }
 
                 if (url === '/api/import_drop_resources' && method === 'POST') {
-                    await invoke('import_dropped_resources', { 
+                    const report = await invoke('import_dropped_resources', { 
                         folder: body.folder, 
                         paths: body.paths,
-                        copyMode: body.copyMode 
+                        copyMode: body.copyMode,
+                        conflict: body.conflict || null
                     });
-                    return { status: 'success' };
+                    return { status: 'success', report };
+                }
+
+                if ((url === '/api/move_items' || url === '/api/copy_items') && method === 'POST') {
+                    const report = await invoke(url === '/api/move_items' ? 'move_items' : 'copy_items', {
+                        section: body.section,
+                        items: body.items,
+                        destSection: body.destSection,
+                        destFolder: body.destFolder,
+                        conflict: body.conflict || null
+                    });
+                    return { status: 'success', report };
+                }
+
+                // --- PAPELERA ---
+                if (url === '/api/trash' && method === 'GET') {
+                    const clients = await invoke('list_trashed_items');
+                    return { status: 'success', clients };
+                }
+                if (url === '/api/trash/restore' && method === 'POST') {
+                    const count = await invoke('restore_trashed_items', { ids: body.ids });
+                    return { status: 'success', count };
+                }
+                if (url === '/api/trash/purge' && method === 'POST') {
+                    const count = await invoke('purge_trashed_items', { ids: body.ids });
+                    return { status: 'success', count };
                 }
 
                 console.warn('Ruta API no implementada en adaptador Tauri:', url);
@@ -196,10 +248,22 @@ document.addEventListener('DOMContentLoaded', async () => {
 
             } catch (error) {
                 console.error('Error en Tauri Invoke:', error);
-                return { status: 'error', error: error.toString() };
+                // Los comandos devuelven { code, message, path } (ArchiError)
+                return {
+                    status: 'error',
+                    error: window.utils.errorMessage(error),
+                    code: error && error.code ? error.code : null,
+                    path: error && error.path ? error.path : null
+                };
             }
         },
 
+        // Texto para mostrar de un error de invoke (ArchiError o texto plano)
+        errorMessage(error) {
+            if (error && typeof error === 'object' && error.message) return error.message;
+            return String(error);
+        },
+
         convertFileSrc(filePath) {
             if (window.__TAURI__ && window.__TAURI__.core && window.__TAURI__.core.convertFileSrc) {
                 return window.__TAURI__.core.convertFileSrc(filePath);
@@ -219,7 +283,7 @@ document.addEventListener('DOMContentLoaded', async () => {
                  
                  const imageExts = ['jpg', 'jpeg', 'png', 'gif', 'webp', 'svg', 'bmp', 'ico'];
                  const videoExts = ['mp4', 'webm', 'ogg', 'mov'];
-                 const nativeThumbExts = ['psd', 'psb', 'ai', 'indd', 'pdf', 'eps', 'tiff', 'tif', 'raw', 'dng', 'cdr', 'doc', 'docx', 'xls', 'xlsx', 'ppt', 'pptx'];
+                 const nativeThumbExts = ['psd', 'psb', 'ai', 'indd', 'pdf', 'eps', 'tiff', 'tif', 'raw', 'dng', 'cr2', 'nef', 'arw', 'cdr', 'doc', 'docx', 'xls', 'xlsx', 'ppt', 'pptx'];
                  const textPreviewExts = ['txt', 'rtf', 'md', 'json', 'xml', 'log', 'ini', 'cfg', 'csv'];
                  const documentExts = [];
                  const zipExts = ['zip', 'rar', '7z', 'tar', 'gz'];
@@ -263,7 +327,7 @@ document.addEventListener('DOMContentLoaded', async () => {
                      : file.name.split('.').pop().toLowerCase();
             
             const textPreviewExts = ['txt', 'rtf', 'md', 'json', 'xml', 'log', 'ini', 'cfg', 'csv'];
-            const nativeThumbExts = ['psd', 'psb', 'ai', 'indd', 'pdf', 'eps', 'tiff', 'tif', 'raw', 'dng', 'cdr', 'doc', 'docx', 'xls', 'xlsx', 'ppt', 'pptx'];
+            const nativeThumbExts = ['psd', 'psb', 'ai', 'indd', 'pdf', 'eps', 'tiff', 'tif', 'raw', 'dng', 'cr2', 'nef', 'arw', 'cdr', 'doc', 'docx', 'xls', 'xlsx', 'ppt', 'pptx'];
             const imageExts = ['jpg', 'jpeg', 'png', 'gif', 'webp', 'svg', 'bmp', 'ico'];
     
             if (textPreviewExts.includes(ext)) {
@@ -309,12 +373,16 @@ document.addEventListener('DOMContentLoaded', async () => {
                  }
             } else if (nativeThumbExts.includes(ext) || imageExts.includes(ext)) {
                  if (window.__TAURI__ && window.__TAURI__.core) {
-                     window.__TAURI__.core.invoke('generate_thumbnail', { path: file.path })
-                        .then(base64 => {
+                     const thumbMode = this.thumbnailMode === 'fill' ? 'fill' : 'fit';
+                     const thumbImg = containerElement.querySelector('.adobe-thumb');
+                     if (thumbImg) thumbImg.dataset.path = file.path;
+
+                     window.__TAURI__.core.invoke('generate_thumbnail', { path: file.path, size: 'small', mode: thumbMode })
+                        .then(url => {
                             const img = containerElement.querySelector('.adobe-thumb');
                             if (img) {
-                                const mode = this.thumbnailMode === 'fit' ? 'fit' : 'fill';
-                                img.src = base64;
+                                const mode = thumbMode;
+                                img.src = url;
                                 img.classList.remove('thumb-opacity-low');
                                 img.style.padding = '0';
                                 img.style.margin = '0';
@@ -331,7 +399,7 @@ document.addEventListener('DOMContentLoaded', async () => {
                             if (imageExts.includes(ext)) {
                                 const img = containerElement.querySelector('.adobe-thumb');
                                 if (img) {
-                                    const mode = this.thumbnailMode === 'fit' ? 'fit' : 'fill';
+                                    const mode = this.thumbnailMode === 'fill' ? 'fill' : 'fit';
                                     img.src = this.convertFileSrc(file.path);
                                     img.classList.remove('thumb-opacity-low');
                                     img.style.opacity = '1';
@@ -347,6 +415,18 @@ document.addEventListener('DOMContentLoaded', async () => {
             }
         },
 
+        // Vuelve a pedir las miniaturas visibles (p. ej. al cambiar entre fit y fill)
+        refreshThumbnails() {
+            if (!window.__TAURI__ || !window.__TAURI__.core) return;
+            const mode = this.thumbnailMode === 'fill' ? 'fill' : 'fit';
+
+            document.querySelectorAll('.adobe-thumb[data-path]').forEach(img => {
+                window.__TAURI__.core.invoke('generate_thumbnail', { path: img.dataset.path, size: 'small', mode })
+                    .then(url => { img.src = url; })
+                    .catch(() => {});
+            });
+        },
+
         async refreshLateralBar() {
             const result = await this.apiCall('/api/clients', null, 'GET');
             if (result.status === 'success') {
@@ -387,7 +467,8 @@ document.addEventListener('DOMContentLoaded', async () => {
                 });
                 
                 const clientList = result.clients.map(c => typeof c === 'object' ? c.name : c);
-                if (activeName && !clientList.includes(activeName)) {
+                const inSettings = activeTab && activeTab.classList.contains('settings-tab');
+                if (activeName && !inSettings && !clientList.includes(activeName)) {
                     const firstTab = container.querySelector('.lateral-tab:not(.settings-tab)');
                     if (firstTab) firstTab.click();
                 } else if (activeName) {
@@ -450,9 +531,25 @@ document.addEventListener('DOMContentLoaded', async () => {
             if (moduleId !== 'M-Settings') {
                 this.apiCall(`/update_module/${moduleId}`, null, 'GET');
             }
+            this.watchActiveClient();
             document.dispatchEvent(new CustomEvent('moduleActivated', { detail: { moduleId, source } }));
         },
 
+        // Vigilar en el backend las carpetas del cliente activo (evento fs-changed)
+        watchActiveClient() {
+            if (!window.__TAURI__) return;
+            const tab = document.querySelector('.lateral-tab.active:not(.settings-tab)') || lastActiveLateralTab;
+            const textEl = tab ? tab.querySelector('.tab-text') : null;
+            const name = textEl ? textEl.textContent : null;
+            if (!name || name === this.watchedClient) return;
+
+            this.watchedClient = name;
+            window.__TAURI__.core.invoke('watch_client', { name }).catch(err => {
+                console.error('Error vigilando el cliente:', err);
+                this.watchedClient = null;
+            });
+        },
+
         attachNativeFileDrag(element, getPath, opts = {}) {
             if (!element || typeof getPath !== 'function') return;
             if (element.__archiNativeDragInstalled) return;
@@ -521,8 +618,9 @@ document.addEventListener('DOMContentLoaded', async () => {
                     document.body.classList.remove('lowercase-path');
                 }
 
+                // Como en el backend: cualquier valor que no sea "fill" es fit
                 const rawMode = config.thumbnail_mode || 'fit';
-                const mode = String(rawMode).toLowerCase() === 'fit' ? 'fit' : 'fill';
+                const mode = String(rawMode).toLowerCase() === 'fill' ? 'fill' : 'fit';
                 this.thumbnailMode = mode;
             
                 document.dispatchEvent(new CustomEvent('configApplied', { detail: config }));
@@ -574,6 +672,42 @@ document.addEventListener('DOMContentLoaded', async () => {
                     });
                 }
 
+                // Cambios en disco (Finder, Photoshop...): recargar el grid si muestra la carpeta afectada
+                if (window.__TAURI__.event) {
+                    let fsReloadTimer = null;
+                    window.__TAURI__.event.listen('fs-changed', (event) => {
+                        const changes = event.payload || [];
+
+                        const bibliotecaModule = document.getElementById('M-Biblioteca');
+                        const recursosModule = document.getElementById('M-Recursos');
+
+                        let moduleId = null;
+                        let section = null;
+                        let folder = null;
+
+                        if (bibliotecaModule && bibliotecaModule.classList.contains('active')) {
+                            const workGrid = document.getElementById('grid-library');
+                            const gridFinder = document.getElementById('grid-work');
+                            const activeGrid = (workGrid && workGrid.style.display !== 'none') ? workGrid : gridFinder;
+                            folder = activeGrid ? activeGrid.getAttribute('data-current-folder') : null;
+                            moduleId = 'M-Biblioteca';
+                            section = 'Biblioteca';
+                        } else if (recursosModule && recursosModule.classList.contains('active')) {
+                            const resourcesGrid = document.getElementById('resources-grid');
+                            folder = resourcesGrid ? resourcesGrid.getAttribute('data-current-folder') : null;
+                            moduleId = 'M-Recursos';
+                            section = 'Recursos';
+                        }
+
+                        if (!folder || !changes.some(c => c.section === section && c.folder === folder)) return;
+
+                        clearTimeout(fsReloadTimer);
+                        fsReloadTimer = setTimeout(() => {
+                            document.dispatchEvent(new CustomEvent('moduleActivated', { detail: { moduleId, path: folder } }));
+                        }, 150);
+                    });
+                }
+
                 await invoke('ensure_user_setup');
                 console.log('Setup de usuario verificado/completado');
                 
@@ -713,6 +847,8 @@ document.addEventListener('DOMContentLoaded', async () => {
             const tab = e.target.closest('.lateral-tab');
             if (!tab || tab.classList.contains('settings-tab')) return;
             handleLateralTabSelection(tab);
+            // Vigilar el cliente elegido aunque el módulo activo no cambie
+            window.utils.watchActiveClient();
         });
     }
 
@@ -841,12 +977,14 @@ document.addEventListener('DOMContentLoaded', async () => {
     const modalConfirm = document.getElementById('modal-confirm');
     const modalCancel = document.getElementById('modal-cancel');
     const modalToggleExtension = document.getElementById('modal-toggle-extension');
+    const modalSecondary = document.getElementById('modal-secondary');
 
     let currentModalAction = null;
+    let currentSecondaryAction = null;
     let existingItems = [];
 
     window.showModal = function(options) {
-        const { title, placeholder, confirmText, action, initialValue, message, showInput = true, validationValue = null, validationItems = null, showExtensionToggle = false, originalExtension = '' } = options;
+        const { title, placeholder, confirmText, action, initialValue, message, showInput = true, validationValue = null, validationItems = null, showExtensionToggle = false, originalExtension = '', secondaryText = null, secondaryAction = null } = options;
         
         // Si se pasan items específicos para validar, usarlos. Si no, obtenerlos del grid visible actual.
         if (validationItems && Array.isArray(validationItems)) {
@@ -960,6 +1098,16 @@ document.addEventListener('DOMContentLoaded', async () => {
         modalConfirm.textContent = confirmText || 'Confirmar';
         currentModalAction = action;
 
+        // Tercer botón opcional (p. ej. "Conservar ambos" en conflictos)
+        if (secondaryText && secondaryAction) {
+            modalSecondary.textContent = secondaryText;
+            modalSecondary.style.display = '';
+            currentSecondaryAction = secondaryAction;
+        } else {
+            modalSecondary.style.display = 'none';
+            currentSecondaryAction = null;
+        }
+
         // Estado inicial del botón confirmar
         if (showInput) {
             const initialVal = (initialValue || '').trim();
@@ -1056,6 +1204,7 @@ document.addEventListener('DOMContentLoaded', async () => {
     function closeModal() {
         modal.classList.remove('show');
         currentModalAction = null;
+        currentSecondaryAction = null;
     }
 
     modalConfirm.addEventListener('click', () => {
@@ -1078,6 +1227,12 @@ document.addEventListener('DOMContentLoaded', async () => {
 
     modalCancel.addEventListener('click', closeModal);
 
+    modalSecondary.addEventListener('click', () => {
+        const action = currentSecondaryAction;
+        closeModal();
+        if (action) action();
+    });
+
     // Efecto de resplandor radial siguiendo al mouse (idéntico al menú contextual)
     modal.addEventListener('mousemove', (e) => {
         const container = modal.querySelector('.modal-container');
@@ -1177,41 +1332,65 @@ document.addEventListener('DOMContentLoaded', async () => {
             const folder = activeGrid.getAttribute('data-current-folder');
             if (!folder) return;
 
-            try {
-                const copyMode = isAltPressed || dropCopyMode;
-                let result;
-                if (moduleId === 'M-Recursos') {
-                    result = await window.utils.apiCall('/api/import_drop_resources', { 
-                        folder, 
-                        paths,
-                        copyMode
-                    }, 'POST');
-                } else {
-                    result = await window.utils.apiCall('/api/import_drop', { 
-                        folder, 
-                        paths,
-                        copyMode
+            const copyMode = isAltPressed || dropCopyMode;
+            const importUrl = moduleId === 'M-Recursos' ? '/api/import_drop_resources' : '/api/import_drop';
+
+            // Los elementos que ya existen en el destino vuelven como 'conflict'
+            // y se pregunta qué hacer con ellos antes de repetir la importación
+            const runImport = async (importPaths, conflict) => {
+                try {
+                    let result = await window.utils.apiCall(importUrl, {
+                        folder,
+                        paths: importPaths,
+                        copyMode,
+                        conflict
                     }, 'POST');
-                }
 
-                if (result && result.status === 'success') {
-                    document.dispatchEvent(new CustomEvent('moduleActivated', { detail: { moduleId, path: folder } }));
-                } else if (result && result.status === 'error') {
-                    const msg = result.error || 'No se pudo copiar los archivos';
-                    if (msg.includes('Operation not permitted')) {
-                        window.utils.showError(
-                            'Permiso denegado al leer archivo',
-                            'macOS no permite acceder a la ubicación de origen. Prueba copiar desde otra carpeta o revisa los permisos de privacidad en Preferencias del Sistema.'
-                        );
-                    } else {
-                        window.utils.showError('Error al importar archivos', msg);
+                    // Un elemento fallido se muestra como error aunque otros se hayan copiado
+                    const items = result && result.report ? result.report.items : [];
+                    const failed = items.find(item => item.status === 'failed');
+                    const conflicts = items.filter(item => item.status === 'conflict').map(item => item.source);
+
+                    if (result && result.status === 'success') {
+                        document.dispatchEvent(new CustomEvent('moduleActivated', { detail: { moduleId, path: folder } }));
+                    }
+                    if (failed) {
+                        result = { status: 'error', error: window.utils.errorMessage(failed.error), code: failed.error && failed.error.code };
                     }
+                    if (result && result.status === 'error') {
+                        const msg = result.error || 'No se pudo copiar los archivos';
+                        if (result.code === 'permission_denied' || msg.includes('Operation not permitted')) {
+                            window.utils.showError(
+                                'Permiso denegado al leer archivo',
+                                'macOS no permite acceder a la ubicación de origen. Prueba copiar desde otra carpeta o revisa los permisos de privacidad en Preferencias del Sistema.'
+                            );
+                        } else {
+                            window.utils.showError('Error al importar archivos', msg);
+                        }
+                    } else if (conflicts.length > 0) {
+                        askConflict(conflicts);
+                    }
+                } catch (error) {
+                    console.error('Error en import_drop', error);
+                    window.utils.showError('Error al importar archivos', window.utils.errorMessage(error));
                 }
-            } catch (error) {
-                console.error('Error en import_drop', error);
-                const msg = error && error.toString ? error.toString() : String(error);
-                window.utils.showError('Error al importar archivos', msg);
-            }
+            };
+
+            const askConflict = (conflicts) => {
+                const names = conflicts.map(source => escapeHtml(source.split(/[\\/]/).pop()));
+                const shown = names.slice(0, 5).join(', ') + (names.length > 5 ? '…' : '');
+                window.showModal({
+                    title: conflicts.length === 1 ? 'El elemento ya existe' : `${conflicts.length} elementos ya existen`,
+                    message: `Ya hay elementos con el mismo nombre en el destino: ${shown}. Los que se sustituyan irán a la papelera.`,
+                    confirmText: 'Reemplazar',
+                    action: () => runImport(conflicts, 'replace'),
+                    secondaryText: 'Conservar ambos',
+                    secondaryAction: () => runImport(conflicts, 'keep_both'),
+                    showInput: false
+                });
+            };
+
+            await runImport(paths, 'ask');
         };
 
         window.__TAURI__.event.listen('tauri://drag-enter', () => {
@@ -1232,6 +1411,47 @@ document.addEventListener('DOMContentLoaded', async () => {
         window.__TAURI__.event.listen('tauri://drag-drop', handleExternalDrop);
         window.__TAURI__.event.listen('tauri://file-drop', handleExternalDrop);
 
+        // Progreso de copias e importaciones (evento transfer-progress). Solo se
+        // muestra si la transferencia dura más de un instante
+        const transferPanel = document.getElementById('transfer-progress');
+        const transferLabel = document.getElementById('transfer-progress-label');
+        const transferFill = document.getElementById('transfer-progress-fill');
+        const transferCancel = document.getElementById('transfer-progress-cancel');
+        let transferJobId = null;
+        let transferShowTimer = null;
+
+        window.__TAURI__.event.listen('transfer-progress', (event) => {
+            const progress = event.payload;
+            transferJobId = progress.job_id;
+
+            if (progress.items_done >= progress.items_total) {
+                clearTimeout(transferShowTimer);
+                transferShowTimer = null;
+                transferPanel.classList.remove('show');
+                return;
+            }
+
+            const percent = progress.bytes_total > 0
+                ? Math.min(100, Math.round(progress.bytes_done / progress.bytes_total * 100))
+                : 0;
+            transferLabel.textContent = `${progress.items_done + 1} de ${progress.items_total} · ${window.utils.formatSize(progress.bytes_done)} de ${window.utils.formatSize(progress.bytes_total)}`;
+            transferFill.style.width = `${percent}%`;
+
+            if (!transferPanel.classList.contains('show') && !transferShowTimer) {
+                transferShowTimer = setTimeout(() => {
+                    transferShowTimer = null;
+                    transferPanel.classList.add('show');
+                }, 400);
+            }
+        });
+
+        transferCancel.addEventListener('click', () => {
+            if (transferJobId === null) return;
+            window.__TAURI__.core.invoke('cancel_transfer', { jobId: transferJobId }).catch(err => {
+                console.error('Error cancelando la transferencia:', err);
+            });
+        });
+
         // Listener para cambiar de módulo desde el menú nativo
         window.__TAURI__.event.listen('switch-module', (event) => {
             const moduleId = event.payload;
@@ -1242,6 +1462,58 @@ document.addEventListener('DOMContentLoaded', async () => {
             }
         });
 
+        // Cambio de biblioteca (Ajustes > Bibliotecas): otros clientes y otra
+        // carpeta que vigilar. El cliente abierto se conserva si también existe
+        window.__TAURI__.event.listen('library-changed', async () => {
+            const previous = lastActiveLateralTab ? lastActiveLateralTab.querySelector('.tab-text')?.textContent.trim() : null;
+            window.utils.watchedClient = null;
+            await window.utils.refreshLateralBar();
+
+            const clientTabs = Array.from(document.querySelectorAll('.lateral-tab:not(.settings-tab)'));
+            lastActiveLateralTab = clientTabs.find(t => t.querySelector('.tab-text')?.textContent.trim() === previous) || clientTabs[0] || null;
+
+            // Desde Ajustes se vigila al volver (activateModule)
+            if (settingsTab && settingsTab.classList.contains('show')) return;
+            const activeTopTab = document.querySelector('.top-tab.active');
+            const moduleId = activeTopTab ? activeTopTab.getAttribute('data-module') : 'M-Inicio';
+            window.utils.activateModule(moduleId, lastActiveLateralTab, 'restore');
+        });
+
+        // Deshacer/Rehacer (Cmd+Z / Cmd+Shift+Z): el texto si se está editando, si no
+        // la última operación de archivos del historial del backend
+        window.__TAURI__.event.listen('history-command', async (event) => {
+            const action = event.payload;
+            if (isTextEditingElement(document.activeElement)) {
+                document.execCommand(action);
+                return;
+            }
+
+            try {
+                const label = await window.__TAURI__.core.invoke(action === 'undo' ? 'undo_last' : 'redo');
+                if (!label) return;
+                console.log(`${action === 'undo' ? 'Deshecho' : 'Rehecho'}: ${label}`);
+
+                await window.utils.refreshLateralBar();
+                const activeTopTab = document.querySelector('.top-tab.active');
+                const moduleId = activeTopTab ? activeTopTab.getAttribute('data-module') : null;
+                let grid = null;
+                if (moduleId === 'M-Biblioteca') {
+                    const workGrid = document.getElementById('grid-library');
+                    const gridFinder = document.getElementById('grid-work');
+                    grid = (workGrid && workGrid.style.display !== 'none') ? workGrid : gridFinder;
+                } else if (moduleId === 'M-Recursos') {
+                    grid = document.getElementById('resources-grid');
+                }
+                const folder = grid ? grid.getAttribute('data-current-folder') : null;
+                if (folder) {
+                    document.dispatchEvent(new CustomEvent('moduleActivated', { detail: { moduleId, path: folder } }));
+                }
+            } catch (error) {
+                const title = action === 'undo' ? 'No se pudo deshacer' : 'No se pudo rehacer';
+                window.utils.showError(title, window.utils.errorMessage(error));
+            }
+        });
+
         // Listener para crear cliente (Cmd+Shift+N)
         window.__TAURI__.event.listen('trigger-create-client', () => {
             console.log('Trigger create client shortcut');